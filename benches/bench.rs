@@ -1,13 +1,16 @@
 use bencher::{benchmark_group, benchmark_main, black_box, Bencher};
 use rand::prelude::*;
-use sobol_burley::{sample, sample_4d};
+use sobol_burley::{
+    sample, sample_4d, sample_4d_dims, sample_4d_limited, sample_limited, SampleContext,
+    SeededSampler,
+};
 
 //----
 
 fn gen_1000_samples_4d(bench: &mut Bencher) {
     bench.iter(|| {
         for i in 0..250u32 {
-            black_box(sample_4d(i, 0, 1234567890));
+            black_box(sample_4d(i, 0, black_box(1234567890)));
         }
     });
 }
@@ -31,7 +34,7 @@ fn gen_1000_samples_incoherent_4d(bench: &mut Bencher) {
 fn gen_1000_samples(bench: &mut Bencher) {
     bench.iter(|| {
         for i in 0..1000u32 {
-            black_box(sample(i, 0, 1234567890));
+            black_box(sample(i, 0, black_box(1234567890)));
         }
     });
 }
@@ -52,6 +55,113 @@ fn gen_1000_samples_incoherent(bench: &mut Bencher) {
     });
 }
 
+fn gen_1000_samples_seeded(bench: &mut Bencher) {
+    bench.iter(|| {
+        let sampler = SeededSampler::new(black_box(1234567890));
+        for i in 0..1000u32 {
+            black_box(sampler.sample(i, 0));
+        }
+    });
+}
+
+fn gen_1000_samples_seeded_4d(bench: &mut Bencher) {
+    bench.iter(|| {
+        let sampler = SeededSampler::new(black_box(1234567890));
+        for i in 0..250u32 {
+            black_box(sampler.sample_4d(i, 0));
+        }
+    });
+}
+
+fn gen_1000_samples_seeded_batch(bench: &mut Bencher) {
+    let mut out = [0.0f32; 1000];
+    bench.iter(|| {
+        let sampler = SeededSampler::new(black_box(1234567890));
+        sampler.sample_batch(0, 0, &mut out);
+        black_box(&out);
+    });
+}
+
+fn gen_1000_samples_seeded_batch_4d(bench: &mut Bencher) {
+    let mut out = [[0.0f32; 4]; 250];
+    bench.iter(|| {
+        let sampler = SeededSampler::new(black_box(1234567890));
+        sampler.sample_4d_batch(0, 0, &mut out);
+        black_box(&out);
+    });
+}
+
+fn gen_1000_dimensions(bench: &mut Bencher) {
+    bench.iter(|| {
+        for d in 0..250u32 {
+            black_box(sample(black_box(5), d, 1234567890));
+        }
+        for d in 0..250u32 {
+            black_box(sample(black_box(6), d, 1234567890));
+        }
+        for d in 0..250u32 {
+            black_box(sample(black_box(7), d, 1234567890));
+        }
+        for d in 0..250u32 {
+            black_box(sample(black_box(8), d, 1234567890));
+        }
+    });
+}
+
+fn gen_1000_dimensions_context(bench: &mut Bencher) {
+    bench.iter(|| {
+        for i in 5..9u32 {
+            let ctx = SampleContext::new(black_box(i), 1234567890);
+            for d in 0..250u32 {
+                black_box(ctx.dim(d));
+            }
+        }
+    });
+}
+
+fn gen_1000_dimensions_context_4d(bench: &mut Bencher) {
+    bench.iter(|| {
+        for i in 5..9u32 {
+            let ctx = SampleContext::new(black_box(i), 1234567890);
+            for ds in 0..62u32 {
+                black_box(ctx.dims_4d(ds));
+            }
+        }
+    });
+}
+
+fn gen_1000_samples_limited(bench: &mut Bencher) {
+    bench.iter(|| {
+        for i in 0..1000u32 {
+            black_box(sample_limited(i, 0, black_box(1234567890), 10));
+        }
+    });
+}
+
+fn gen_1000_samples_limited_4d(bench: &mut Bencher) {
+    bench.iter(|| {
+        for i in 0..250u32 {
+            black_box(sample_4d_limited(i, 0, black_box(1234567890), 8));
+        }
+    });
+}
+
+fn gen_1000_samples_4d_dims_aligned(bench: &mut Bencher) {
+    bench.iter(|| {
+        for i in 0..250u32 {
+            black_box(sample_4d_dims(i, [4, 5, 6, 7], black_box(1234567890)));
+        }
+    });
+}
+
+fn gen_1000_samples_4d_dims_gathered(bench: &mut Bencher) {
+    bench.iter(|| {
+        for i in 0..250u32 {
+            black_box(sample_4d_dims(i, [0, 5, 17, 200], black_box(1234567890)));
+        }
+    });
+}
+
 //----
 
 benchmark_group!(
@@ -60,5 +170,16 @@ benchmark_group!(
     gen_1000_samples_incoherent,
     gen_1000_samples_4d,
     gen_1000_samples_incoherent_4d,
+    gen_1000_samples_seeded,
+    gen_1000_samples_seeded_4d,
+    gen_1000_samples_seeded_batch,
+    gen_1000_samples_seeded_batch_4d,
+    gen_1000_dimensions,
+    gen_1000_dimensions_context,
+    gen_1000_dimensions_context_4d,
+    gen_1000_samples_limited,
+    gen_1000_samples_limited_4d,
+    gen_1000_samples_4d_dims_aligned,
+    gen_1000_samples_4d_dims_gathered,
 );
 benchmark_main!(benches);
@@ -64,11 +64,166 @@ fn main() {
         }
         f.write_all("  ],\n".as_bytes()).unwrap();
     }
+    f.write_all("];\n\n".as_bytes()).unwrap();
+
+    // Write the table of dimension pairs, best 2D projections first.
+    let pairs = best_pairs(&vectors);
+    f.write_all(
+        format!(
+            "/// The number of dimension pairs in [`PAIRS_2D`].\n\
+             pub const NUM_PAIRS_2D: u32 = {};\n\n",
+            pairs.len()
+        )
+        .as_bytes(),
+    )
+    .unwrap();
+    f.write_all(
+        format!(
+            "/// Disjoint pairs of dimensions, ordered from best to worst 2D \
+             projection.\n\
+             ///\n\
+             /// See [`sample_2d()`] for details.\n\
+             pub const PAIRS_2D: [[u32; 2]; {}] = [\n",
+            pairs.len()
+        )
+        .as_bytes(),
+    )
+    .unwrap();
+    for &(_, _, d1, d2) in pairs.iter() {
+        f.write_all(format!("    [{}, {}],\n", d1, d2).as_bytes())
+            .unwrap();
+    }
+    f.write_all("];\n\n".as_bytes()).unwrap();
+    f.write_all(
+        format!(
+            "/// The t-values of the first 2^{} points of the pairs in \
+             [`PAIRS_2D`].\n\
+             ///\n\
+             /// A t-value of `t` means that every aligned block of 2^m \
+             points of the pair\n\
+             /// is stratified into 2^(m-t) elementary intervals of every \
+             shape, and zero\n\
+             /// is perfect stratification.\n\
+             pub const PAIRS_2D_T_VALUES: [u32; {}] = [\n",
+            SOBOL_DEPTH,
+            pairs.len()
+        )
+        .as_bytes(),
+    )
+    .unwrap();
+    for &(t, _, _, _) in pairs.iter() {
+        f.write_all(format!("    {},\n", t).as_bytes()).unwrap();
+    }
     f.write_all("];\n".as_bytes()).unwrap();
 }
 
 //======================================================================
 
+/// Greedily pair up the dimensions by the quality of their 2D projections.
+///
+/// Returns `(t-value, secondary t-value, dimension, dimension)` for each
+/// pair, best first.  Pairs are ranked by the t-value of all 2^16 points,
+/// with ties broken by the t-value of the first 2^8 points and then by
+/// dimension number.
+fn best_pairs(vectors: &[[SobolInt; SOBOL_DEPTH]]) -> Vec<(u32, u32, usize, usize)> {
+    let rows: Vec<_> = vectors.iter().map(generator_rows).collect();
+
+    let mut all_pairs = Vec::new();
+    for d1 in 0..vectors.len() {
+        for d2 in (d1 + 1)..vectors.len() {
+            let t = t_value(&rows[d1], &rows[d2], SOBOL_DEPTH);
+            let t_half = t_value(&rows[d1], &rows[d2], SOBOL_DEPTH / 2);
+            all_pairs.push((t, t_half, d1, d2));
+        }
+    }
+    all_pairs.sort_unstable();
+
+    let mut used = vec![false; vectors.len()];
+    let mut pairs = Vec::new();
+    for &(t, t_half, d1, d2) in all_pairs.iter() {
+        if !used[d1] && !used[d2] {
+            used[d1] = true;
+            used[d2] = true;
+            pairs.push((t, t_half, d1, d2));
+        }
+    }
+
+    pairs
+}
+
+/// The rows of a dimension's generator matrix, restricted to the first
+/// `SOBOL_DEPTH` columns.  Row `i` gives the contribution of each index bit
+/// to the `i`th most significant bit of the output.
+fn generator_rows(v: &[SobolInt; SOBOL_DEPTH]) -> [u32; SOBOL_DEPTH] {
+    let mut rows = [0u32; SOBOL_DEPTH];
+    for (i, row) in rows.iter_mut().enumerate() {
+        for (j, &column) in v.iter().enumerate() {
+            *row |= ((column >> (SOBOL_BITS - 1 - i)) & 1) << j;
+        }
+    }
+    rows
+}
+
+/// The t-value of the first 2^m points of the 2D projection with the given
+/// generator matrix rows.
+///
+/// The points are a (t,m,2)-net when, for every split of `m - t` into
+/// `a + b`, the first `a` rows of the first matrix together with the first
+/// `b` rows of the second are linearly independent over GF(2).
+fn t_value(rows1: &[u32; SOBOL_DEPTH], rows2: &[u32; SOBOL_DEPTH], m: usize) -> u32 {
+    let mask = ((1u64 << m) - 1) as u32;
+
+    // For each `a`, the largest `b` such that the rows are independent.
+    let mut max_b = [0usize; SOBOL_DEPTH + 1];
+    let mut basis = Basis::default();
+    for a in 0..=m {
+        if a > 0 {
+            // Sobol generator matrices are always non-singular.
+            let independent = basis.insert(rows1[a - 1] & mask);
+            assert!(independent, "Degenerate generator matrix.");
+        }
+        let mut basis2 = basis;
+        let mut b = 0;
+        while a + b < m && basis2.insert(rows2[b] & mask) {
+            b += 1;
+        }
+        max_b[a] = b;
+    }
+
+    // The largest `k = m - t` that works for every split.
+    let mut k = 0;
+    while k < m && (0..=(k + 1)).all(|a| a + max_b[a] > k) {
+        k += 1;
+    }
+
+    (m - k) as u32
+}
+
+/// An incrementally built basis of GF(2) vectors, in echelon form.
+#[derive(Copy, Clone, Default)]
+struct Basis {
+    /// The basis vector with each leading bit, or zero if there is none.
+    vectors: [u32; SOBOL_DEPTH],
+}
+
+impl Basis {
+    /// Add `x` to the basis.  Returns false if it's linearly dependent on
+    /// the vectors already in it.
+    fn insert(&mut self, mut x: u32) -> bool {
+        while x != 0 {
+            let bit = 31 - x.leading_zeros() as usize;
+            if self.vectors[bit] == 0 {
+                self.vectors[bit] = x;
+                return true;
+            }
+            x ^= self.vectors[bit];
+        }
+        false
+    }
+}
+
+//======================================================================
+
 // The following is adapted from the code on this webpage:
 //
 // http://web.maths.unsw.edu.au/~fkuo/sobol/
@@ -46,12 +46,12 @@ where
     }
 
     let mut f = File::create(filename).unwrap();
-    f.write(format!("P1\n{} {}\n\n", X_RES, Y_RES).as_bytes())
+    f.write_all(format!("P1\n{} {}\n\n", X_RES, Y_RES).as_bytes())
         .unwrap();
     for chunk in image.chunks(80) {
         for pixel in chunk.iter() {
-            f.write(if *pixel == 0 { b"0" } else { b"1" }).unwrap();
+            f.write_all(if *pixel == 0 { b"0" } else { b"1" }).unwrap();
         }
-        f.write(b"\n").unwrap();
+        f.write_all(b"\n").unwrap();
     }
 }
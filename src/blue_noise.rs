@@ -0,0 +1,494 @@
+//! Blue-noise error distribution via optimized per-pixel tiles.
+
+use crate::parts::hash;
+use crate::{sample, sample_4d};
+
+/// The number of test integrands the optimizer uses.
+const NUM_INTEGRANDS: usize = 8;
+
+/// The spatial standard deviation of the optimizer's energy kernel, in
+/// pixels.  From Heitz et al.
+const SIGMA_PIXELS: f32 = 2.1;
+
+/// The radius in pixels of the neighborhood used to compute energies.
+const RADIUS: i32 = 4;
+
+/// Tiles of per-pixel seeds and sample index keys, for distributing the
+/// error of per-pixel estimates as blue noise in screen space.
+///
+/// This follows "A Low-Discrepancy Sampler that Distributes Monte Carlo
+/// Errors as a Blue Noise in Screen Space" by Eric Heitz, Laurent Belcour,
+/// Victor Ostromoukhov, David Coeurjolly, and Jean-Claude Iehl, 2019.  Each
+/// pixel of the tile has a seed, which selects that pixel's scrambling, and
+/// a ranking key, which is XORed into the sample index to choose the order
+/// of the pixel's samples.  The tiles are optimized offline with
+/// [`BlueNoiseOptimizer`] so that neighboring pixels get dissimilar errors,
+/// and then repeated over the image.  Sampling is done with
+/// [`sample_blue_noise()`] and [`sample_4d_blue_noise()`].
+///
+/// The tiles only borrow their data, so they can be built from buffers
+/// optimized at startup, loaded from disk, or embedded in the executable.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BlueNoiseTiles<'a> {
+    width: u32,
+    height: u32,
+    seeds: &'a [u32],
+    ranking_keys: &'a [u32],
+}
+
+impl<'a> BlueNoiseTiles<'a> {
+    /// Create tiles of `width` by `height` pixels from `seeds` and
+    /// `ranking_keys` as produced by [`BlueNoiseOptimizer::optimize()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `height` is zero, or if `seeds` or
+    /// `ranking_keys` doesn't have exactly `width * height` elements.
+    pub fn new(
+        width: u32,
+        height: u32,
+        seeds: &'a [u32],
+        ranking_keys: &'a [u32],
+    ) -> BlueNoiseTiles<'a> {
+        assert!(width > 0 && height > 0);
+        assert_eq!(seeds.len(), width as usize * height as usize);
+        assert_eq!(ranking_keys.len(), width as usize * height as usize);
+
+        BlueNoiseTiles {
+            width,
+            height,
+            seeds,
+            ranking_keys,
+        }
+    }
+
+    /// Compute the sample index and seed for a sample of the given pixel.
+    #[inline(always)]
+    fn lookup(&self, pixel: [u32; 2], sample_index: u32, seed: u32) -> (u32, u32) {
+        // Offsetting the tiles by a random amount gives each seed different
+        // per-pixel sequences, without affecting the blue-noise property.
+        let x = ((pixel[0] % self.width) + (hash(seed) % self.width)) % self.width;
+        let y = ((pixel[1] % self.height) + (hash(seed ^ 0x1b873593) % self.height)) % self.height;
+        let i = (y * self.width + x) as usize;
+
+        (sample_index ^ self.ranking_keys[i], self.seeds[i])
+    }
+}
+
+/// Compute one dimension of a single sample for `pixel`, with the error
+/// distributed as blue noise according to `tiles`.
+///
+/// `sample_index` and `dimension` are the same as in [`sample()`].  `seed`
+/// produces different (but still blue-noise distributed) sequences for each
+/// pixel, for example to decorrelate the frames of an animation.
+///
+/// Only some of the estimates get the blue-noise property, namely those
+/// that use just dimensions 0 and 1 with either:
+///
+/// * the first 2^`log2_samples_per_pixel` samples of each pixel, where
+///   `log2_samples_per_pixel` is the value the tiles were optimized for, or
+/// * just the first sample of each pixel, for previews.
+///
+/// Those are the only cases [`BlueNoiseOptimizer`] optimizes for.  Other
+/// dimensions, and other sample counts (e.g. 2 of 16 samples), still get
+/// the usual per-pixel stratification.  But their errors are distributed
+/// between pixels as white noise, the same as with random per-pixel seeds.
+///
+/// # Panics
+///
+/// Same as [`sample()`].
+#[inline]
+pub fn sample_blue_noise(
+    tiles: &BlueNoiseTiles,
+    pixel: [u32; 2],
+    sample_index: u32,
+    dimension: u32,
+    seed: u32,
+) -> f32 {
+    let (sample_index, pixel_seed) = tiles.lookup(pixel, sample_index, seed);
+    sample(sample_index, dimension, pixel_seed)
+}
+
+/// Same as [`sample_blue_noise()`], except computes four dimensions at
+/// once, the same as [`sample_4d()`].
+///
+/// # Panics
+///
+/// Same as [`sample_4d()`].
+#[inline]
+pub fn sample_4d_blue_noise(
+    tiles: &BlueNoiseTiles,
+    pixel: [u32; 2],
+    sample_index: u32,
+    dimension_set: u32,
+    seed: u32,
+) -> [f32; 4] {
+    let (sample_index, pixel_seed) = tiles.lookup(pixel, sample_index, seed);
+    sample_4d(sample_index, dimension_set, pixel_seed)
+}
+
+/// An offline optimizer for [`BlueNoiseTiles`].
+///
+/// The optimizer estimates a set of 2D test integrands (randomly oriented
+/// step functions) in each pixel, using the first two dimensions.  It then
+/// swaps seeds between pixels, keeping swaps that make the estimates of
+/// nearby pixels less similar, as in Heitz et al.  Finally it does the same
+/// for the ranking keys, using just the first sample of each pixel.
+///
+/// Nothing else is optimized: neither the other dimensions nor sample
+/// counts other than 1 and 2^`log2_samples_per_pixel`.  See
+/// [`sample_blue_noise()`] for what that means for sampling.
+///
+/// It never allocates: all buffers are provided by the caller.
+///
+/// ```rust
+/// # use sobol_burley::{sample_blue_noise, BlueNoiseOptimizer, BlueNoiseTiles};
+/// let optimizer = BlueNoiseOptimizer::new(16, 16, 2);
+///
+/// let mut seeds = [0u32; 16 * 16];
+/// let mut ranking_keys = [0u32; 16 * 16];
+/// let mut scratch = [0.0f32; 16 * 16 * BlueNoiseOptimizer::SCRATCH_PER_PIXEL];
+/// optimizer.optimize(&mut seeds, &mut ranking_keys, &mut scratch);
+///
+/// let tiles = BlueNoiseTiles::new(16, 16, &seeds, &ranking_keys);
+/// let n = sample_blue_noise(&tiles, [100, 200], 0, 0, 0);
+/// ```
+///
+/// Optimizing a 128x128 tile takes a few seconds in release builds.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BlueNoiseOptimizer {
+    /// The width of the tiles, in pixels.
+    pub width: u32,
+
+    /// The height of the tiles, in pixels.
+    pub height: u32,
+
+    /// The log2 of the number of samples per pixel to optimize for.
+    pub log2_samples_per_pixel: u32,
+
+    /// The number of optimization steps per pixel, for each of the two
+    /// optimization phases.
+    pub iterations_per_pixel: u32,
+
+    /// The seed of the optimizer's random choices, including the initial
+    /// seeds and ranking keys.
+    pub seed: u32,
+}
+
+impl BlueNoiseOptimizer {
+    /// The number of `f32`s of scratch space needed per pixel.
+    pub const SCRATCH_PER_PIXEL: usize = NUM_INTEGRANDS;
+
+    /// Create an optimizer for tiles of `width` by `height` pixels, with
+    /// 2^`log2_samples_per_pixel` samples per pixel.
+    ///
+    /// The other settings start out at reasonable defaults.
+    pub fn new(width: u32, height: u32, log2_samples_per_pixel: u32) -> BlueNoiseOptimizer {
+        BlueNoiseOptimizer {
+            width,
+            height,
+            log2_samples_per_pixel,
+            iterations_per_pixel: 32,
+            seed: 0,
+        }
+    }
+
+    /// Run the optimization, writing the results into `seeds` and
+    /// `ranking_keys`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `height` is zero, if `log2_samples_per_pixel`
+    /// is greater than 16, or if the buffers are the wrong size: `seeds` and
+    /// `ranking_keys` must have exactly `width * height` elements, and
+    /// `scratch` exactly `width * height *`
+    /// [`SCRATCH_PER_PIXEL`](BlueNoiseOptimizer::SCRATCH_PER_PIXEL).
+    pub fn optimize(&self, seeds: &mut [u32], ranking_keys: &mut [u32], scratch: &mut [f32]) {
+        let pixel_count = self.width as usize * self.height as usize;
+        assert!(pixel_count > 0);
+        assert!(self.log2_samples_per_pixel <= 16);
+        assert_eq!(seeds.len(), pixel_count);
+        assert_eq!(ranking_keys.len(), pixel_count);
+        assert_eq!(scratch.len(), pixel_count * NUM_INTEGRANDS);
+
+        let spp = 1u32 << self.log2_samples_per_pixel;
+        let steps = pixel_count as u32 * self.iterations_per_pixel;
+        let mut rng = Rng(hash(self.seed ^ 0x3c6ef372));
+
+        // Start from random seeds, with the ranking keys not re-ordering
+        // anything.
+        for (i, (s, r)) in seeds.iter_mut().zip(ranking_keys.iter_mut()).enumerate() {
+            *s = hash(hash(i as u32) ^ self.seed);
+            *r = 0;
+        }
+
+        // Phase one: swap seeds, using the estimates with all the samples.
+        for (i, estimates) in scratch.chunks_exact_mut(NUM_INTEGRANDS).enumerate() {
+            self.estimate(seeds[i], 0, spp, estimates);
+        }
+        let sigma2 = error_sigma2(scratch);
+        for _ in 0..steps {
+            let a = rng.below(pixel_count as u32) as usize;
+            let b = rng.below(pixel_count as u32) as usize;
+            if a == b {
+                continue;
+            }
+
+            let before = self.energy(scratch, a, sigma2) + self.energy(scratch, b, sigma2);
+            swap_estimates(scratch, a, b);
+            let after = self.energy(scratch, a, sigma2) + self.energy(scratch, b, sigma2);
+            if after < before {
+                seeds.swap(a, b);
+            } else {
+                swap_estimates(scratch, a, b);
+            }
+        }
+
+        // Phase two: choose ranking keys, using the estimates with just the
+        // first sample.  The keys only re-order the samples of each pixel,
+        // so this doesn't affect the estimates with all the samples.
+        if spp > 1 {
+            for (i, estimates) in scratch.chunks_exact_mut(NUM_INTEGRANDS).enumerate() {
+                ranking_keys[i] = rng.below(spp);
+                self.estimate(seeds[i], ranking_keys[i], 1, estimates);
+            }
+            let sigma2 = error_sigma2(scratch);
+            let mut backup = [0.0f32; NUM_INTEGRANDS];
+            for _ in 0..steps {
+                let a = rng.below(pixel_count as u32) as usize;
+                let key = rng.below(spp);
+                let range = (a * NUM_INTEGRANDS)..((a + 1) * NUM_INTEGRANDS);
+
+                backup.copy_from_slice(&scratch[range.clone()]);
+                let before = self.energy(scratch, a, sigma2);
+                self.estimate(seeds[a], key, 1, &mut scratch[range.clone()]);
+                let after = self.energy(scratch, a, sigma2);
+                if after < before {
+                    ranking_keys[a] = key;
+                } else {
+                    scratch[range].copy_from_slice(&backup);
+                }
+            }
+        }
+    }
+
+    /// Estimate the test integrands with the first `count` samples of the
+    /// sequence with the given seed and ranking key.
+    fn estimate(&self, seed: u32, ranking_key: u32, count: u32, estimates: &mut [f32]) {
+        for e in estimates.iter_mut() {
+            *e = 0.0;
+        }
+        for i in 0..count {
+            let index = i ^ ranking_key;
+            let p = [sample(index, 0, seed), sample(index, 1, seed)];
+            for (n, e) in estimates.iter_mut().enumerate() {
+                *e += integrand(n as u32, p);
+            }
+        }
+        for e in estimates.iter_mut() {
+            *e /= count as f32;
+        }
+    }
+
+    /// The energy of the pixel at index `i` with respect to its neighbors.
+    /// Neighbors with similar estimates give a higher energy.
+    fn energy(&self, estimates: &[f32], i: usize, sigma2: f32) -> f32 {
+        let (w, h) = (self.width as i32, self.height as i32);
+        let (x, y) = ((i as i32) % w, (i as i32) / w);
+        let e = &estimates[(i * NUM_INTEGRANDS)..((i + 1) * NUM_INTEGRANDS)];
+
+        let mut energy = 0.0;
+        for dy in -RADIUS..=RADIUS {
+            for dx in -RADIUS..=RADIUS {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                // The tiles wrap around.
+                let nx = (x + dx).rem_euclid(w);
+                let ny = (y + dy).rem_euclid(h);
+                let j = (ny * w + nx) as usize;
+                if j == i {
+                    continue;
+                }
+                let ne = &estimates[(j * NUM_INTEGRANDS)..((j + 1) * NUM_INTEGRANDS)];
+
+                let d2_pixels = (dx * dx + dy * dy) as f32;
+                let d2_estimates: f32 = e
+                    .iter()
+                    .zip(ne.iter())
+                    .map(|(a, b)| (a - b) * (a - b))
+                    .sum();
+                energy +=
+                    exp_neg(d2_pixels / (SIGMA_PIXELS * SIGMA_PIXELS) + d2_estimates / sigma2);
+            }
+        }
+
+        energy
+    }
+}
+
+/// A 2D test integrand: a step function across a randomly placed and
+/// oriented line.
+#[inline]
+fn integrand(n: u32, p: [f32; 2]) -> f32 {
+    let h = hash(n ^ 0x5851f42d);
+    let h2 = hash(h);
+    let h3 = hash(h2);
+
+    // The line's normal doesn't need to be normalized, since only the side
+    // of the line matters.
+    let nx = (h as f32 / 4294967296.0) * 2.0 - 1.0;
+    let ny = (h2 as f32 / 4294967296.0) * 2.0 - 1.0;
+    let cx = 0.25 + (h3 >> 16) as f32 / 65536.0 * 0.5;
+    let cy = 0.25 + (h3 & 0xffff) as f32 / 65536.0 * 0.5;
+
+    if (p[0] - cx) * nx + (p[1] - cy) * ny > 0.0 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// The scale of squared differences between the estimates of two pixels,
+/// used to normalize the estimate term of the energy.
+fn error_sigma2(estimates: &[f32]) -> f32 {
+    // Twice the total variance of the estimates is the expected squared
+    // distance between the estimates of two random pixels.  Using a kernel
+    // much wider than that keeps the energy close to a spatially weighted
+    // sum of squared differences, which works better in practice than a
+    // narrow kernel that only penalizes near-identical neighbors.
+    let pixel_count = (estimates.len() / NUM_INTEGRANDS) as f32;
+    let mut total = 0.0;
+    for n in 0..NUM_INTEGRANDS {
+        let values = estimates.iter().skip(n).step_by(NUM_INTEGRANDS);
+        let mean = values.clone().sum::<f32>() / pixel_count;
+        let var = values.map(|v| (v - mean) * (v - mean)).sum::<f32>() / pixel_count;
+        total += var;
+    }
+
+    (total * 2.0 * 16.0).max(1.0e-12)
+}
+
+fn swap_estimates(estimates: &mut [f32], a: usize, b: usize) {
+    for n in 0..NUM_INTEGRANDS {
+        estimates.swap(a * NUM_INTEGRANDS + n, b * NUM_INTEGRANDS + n);
+    }
+}
+
+/// An approximation of `e^-x`, for non-negative `x`.
+///
+/// Computed as `(1 + x/16)^-16`, since `exp()` isn't available without
+/// `std`.
+#[inline(always)]
+fn exp_neg(x: f32) -> f32 {
+    let mut y = 1.0 / (1.0 + x * (1.0 / 16.0));
+    y *= y;
+    y *= y;
+    y *= y;
+    y *= y;
+    y
+}
+
+/// A minimal random number generator for the optimizer's choices.
+struct Rng(u32);
+
+impl Rng {
+    /// A random number in `[0, n)`.
+    #[inline(always)]
+    fn below(&mut self, n: u32) -> u32 {
+        self.0 = self.0.wrapping_add(0x9e3779b9);
+        ((hash(self.0) as u64 * n as u64) >> 32) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: u32 = 16;
+    const PIXELS: usize = (SIZE * SIZE) as usize;
+
+    /// The mean squared error of the test integrands, after averaging each
+    /// 2x2 block of pixels.  Blue-noise errors mostly cancel out under
+    /// such low-pass filtering.
+    fn filtered_error(tiles: &BlueNoiseTiles, spp: u32) -> f32 {
+        // Reference values, from many samples.
+        let mut reference = [0.0f32; NUM_INTEGRANDS];
+        for i in 0..4096 {
+            let p = [sample(i, 0, 0), sample(i, 1, 0)];
+            for (n, r) in reference.iter_mut().enumerate() {
+                *r += integrand(n as u32, p) / 4096.0;
+            }
+        }
+
+        let mut total = 0.0;
+        for by in 0..(SIZE / 2) {
+            for bx in 0..(SIZE / 2) {
+                for (n, &r) in reference.iter().enumerate() {
+                    let mut sum = 0.0;
+                    for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                        for s in 0..spp {
+                            let pixel = [bx * 2 + x, by * 2 + y];
+                            let p = [
+                                sample_blue_noise(tiles, pixel, s, 0, 0),
+                                sample_blue_noise(tiles, pixel, s, 1, 0),
+                            ];
+                            sum += integrand(n as u32, p);
+                        }
+                    }
+                    let err = sum / (4 * spp) as f32 - r;
+                    total += err * err;
+                }
+            }
+        }
+        total
+    }
+
+    #[test]
+    fn optimization_reduces_filtered_error() {
+        // Fewer iterations than the default, to keep debug test runs fast.
+        let optimizer = BlueNoiseOptimizer {
+            iterations_per_pixel: 16,
+            ..BlueNoiseOptimizer::new(SIZE, SIZE, 2)
+        };
+        let mut seeds = [0u32; PIXELS];
+        let mut keys = [0u32; PIXELS];
+        let mut scratch = [0.0f32; PIXELS * NUM_INTEGRANDS];
+        optimizer.optimize(&mut seeds, &mut keys, &mut scratch);
+        let optimized = BlueNoiseTiles::new(SIZE, SIZE, &seeds, &keys);
+
+        // Un-optimized tiles with the same initial seeds.
+        let mut random_seeds = [0u32; PIXELS];
+        for (i, s) in random_seeds.iter_mut().enumerate() {
+            *s = hash(hash(i as u32) ^ optimizer.seed);
+        }
+        let zero_keys = [0u32; PIXELS];
+        let random = BlueNoiseTiles::new(SIZE, SIZE, &random_seeds, &zero_keys);
+
+        assert!(filtered_error(&optimized, 4) < filtered_error(&random, 4) * 0.7);
+        assert!(filtered_error(&optimized, 1) < filtered_error(&random, 1) * 0.7);
+    }
+
+    #[test]
+    fn pixels_keep_their_samples() {
+        // The ranking keys only re-order the samples of each pixel.
+        let optimizer = BlueNoiseOptimizer::new(4, 4, 3);
+        let mut seeds = [0u32; 16];
+        let mut keys = [0u32; 16];
+        let mut scratch = [0.0f32; 16 * NUM_INTEGRANDS];
+        optimizer.optimize(&mut seeds, &mut keys, &mut scratch);
+        let tiles = BlueNoiseTiles::new(4, 4, &seeds, &keys);
+
+        for i in 0..16 {
+            assert!(keys[i] < 8);
+            let pixel = [i as u32 % 4, i as u32 / 4];
+            for s in 0..8 {
+                let a = sample_4d_blue_noise(&tiles, pixel, s, 3, 0);
+                let (index, seed) = tiles.lookup(pixel, s, 0);
+                assert!(index < 8);
+                assert_eq!(a, sample_4d(index, 3, seed));
+                assert_eq!(a[1], sample_blue_noise(&tiles, pixel, s, 13, 0));
+            }
+        }
+    }
+}
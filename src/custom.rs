@@ -0,0 +1,451 @@
+//! A sampler with configurable randomization.
+
+use core::marker::PhantomData;
+
+use crate::parts::{
+    hash, hash_int4, linear_scramble_int4_rev, linear_scramble_rev, shuffle_index_with,
+    sobol_int4_rev, sobol_rev, u32_to_f32_norm, BetterLkHash, Int4, OwenHash,
+};
+use crate::seeded::{scramble_value, scramble_value_int4};
+use crate::SeededSampler;
+
+/// How the values of each dimension are scrambled.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ScrambleMode {
+    /// No scrambling.
+    None,
+
+    /// A random digital shift: the bits of each value are XORed with a
+    /// random number.
+    DigitalShift,
+
+    /// A random linear scramble (Matoušek), via
+    /// [`parts::linear_scramble_rev()`](crate::parts::linear_scramble_rev).
+    LinearMatousek,
+
+    /// A hash-based Owen scramble, using the sampler's
+    /// [`OwenHash`](crate::parts::OwenHash).  With the default hash, this is
+    /// what [`sample()`](crate::sample) uses.
+    Owen,
+}
+
+/// Configuration of the randomization stages of a [`CustomSampler`].
+///
+/// The default configuration is the same as [`sample()`](crate::sample):
+/// the sample index is shuffled, and each dimension is Owen scrambled.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ScrambleConfig {
+    /// Whether to shuffle the sample index, as in Burley's paper.
+    ///
+    /// The shuffle is what makes different seeds produce statistically
+    /// independent sequences rather than just differently scrambled ones.
+    pub index_shuffle: bool,
+
+    /// How to scramble the value of each dimension.
+    pub scramble: ScrambleMode,
+
+    /// The total number of samples that will be taken, if known.
+    ///
+    /// When set (and `index_shuffle` is enabled), the index shuffle is a
+    /// permutation of `0..count` via
+    /// [`parts::shuffle_index()`](crate::parts::shuffle_index), so that
+    /// `count` samples are exactly the first `count` points of the Sobol
+    /// sequence.  Otherwise, sample counts that aren't a power of two get a
+    /// random subset of the next power of two, which is less well
+    /// stratified.  Must be between 1 and 2^16, and only sample indices
+    /// less than `count` may be used.
+    pub sample_count: Option<u32>,
+}
+
+impl ScrambleConfig {
+    /// Plain, un-randomized Sobol.  The seed has no effect.
+    pub const NONE: ScrambleConfig = ScrambleConfig {
+        index_shuffle: false,
+        scramble: ScrambleMode::None,
+        sample_count: None,
+    };
+
+    /// Only a random digital shift.
+    pub const DIGITAL_SHIFT: ScrambleConfig = ScrambleConfig {
+        index_shuffle: false,
+        scramble: ScrambleMode::DigitalShift,
+        sample_count: None,
+    };
+
+    /// Only the index shuffle, with no scrambling of the values.
+    pub const INDEX_SHUFFLE_ONLY: ScrambleConfig = ScrambleConfig {
+        index_shuffle: true,
+        scramble: ScrambleMode::None,
+        sample_count: None,
+    };
+
+    /// Only a random linear (Matoušek) scramble.
+    pub const LINEAR_MATOUSEK: ScrambleConfig = ScrambleConfig {
+        index_shuffle: false,
+        scramble: ScrambleMode::LinearMatousek,
+        sample_count: None,
+    };
+
+    /// Full Owen scrambling plus index shuffling, the same as
+    /// [`sample()`](crate::sample).  This is the default.
+    pub const OWEN: ScrambleConfig = ScrambleConfig {
+        index_shuffle: true,
+        scramble: ScrambleMode::Owen,
+        sample_count: None,
+    };
+}
+
+impl Default for ScrambleConfig {
+    #[inline]
+    fn default() -> ScrambleConfig {
+        ScrambleConfig::OWEN
+    }
+}
+
+/// A Sobol sampler with configurable randomization.
+///
+/// This is mainly useful for comparison studies and debugging.  With the
+/// default [`ScrambleConfig`] it computes exactly the same values as
+/// [`sample()`](crate::sample) and [`sample_4d()`](crate::sample_4d):
+///
+/// ```rust
+/// # use sobol_burley::{sample, CustomSampler, ScrambleConfig};
+/// let owen = CustomSampler::new(42, ScrambleConfig::default());
+/// let plain = CustomSampler::new(42, ScrambleConfig::NONE);
+///
+/// assert_eq!(owen.sample(5, 3), sample(5, 3, 42));
+/// let x = plain.sample(5, 3);
+/// ```
+///
+/// The hash used for Owen scrambling (both of the values and of the index
+/// shuffle) is given by the `H` type parameter, which can be any
+/// [`OwenHash`].  Only the hash changes: the scramble values are still
+/// derived from the seed the same way as in [`sample()`](crate::sample),
+/// so even with the paper's hash the values don't match the paper's code.
+/// For example, to use the hash from Burley's paper:
+///
+/// ```rust
+/// # use sobol_burley::{CustomSampler, ScrambleConfig};
+/// use sobol_burley::parts::LkHash;
+///
+/// let sampler = CustomSampler::<LkHash>::with_hash(42, ScrambleConfig::default());
+/// let x = sampler.sample(5, 3);
+/// ```
+pub struct CustomSampler<H = BetterLkHash> {
+    config: ScrambleConfig,
+    sampler: SeededSampler,
+    hash: PhantomData<H>,
+}
+
+// The trait impls are written out by hand, because deriving them would
+// require `H` to implement the same traits, even though it's only ever used
+// as a type-level choice of hash.
+
+impl<H> Copy for CustomSampler<H> {}
+
+impl<H> Clone for CustomSampler<H> {
+    #[inline]
+    fn clone(&self) -> CustomSampler<H> {
+        *self
+    }
+}
+
+impl<H> PartialEq for CustomSampler<H> {
+    #[inline]
+    fn eq(&self, other: &CustomSampler<H>) -> bool {
+        self.config == other.config && self.sampler == other.sampler
+    }
+}
+
+impl<H> Eq for CustomSampler<H> {}
+
+impl<H> core::hash::Hash for CustomSampler<H> {
+    fn hash<S: core::hash::Hasher>(&self, state: &mut S) {
+        self.config.hash(state);
+        self.sampler.hash(state);
+    }
+}
+
+impl<H> core::fmt::Debug for CustomSampler<H> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("CustomSampler")
+            .field("config", &self.config)
+            .field("sampler", &self.sampler)
+            .field("hash", &core::any::type_name::<H>())
+            .finish()
+    }
+}
+
+impl CustomSampler {
+    /// Create a sampler for the given seed and configuration, using the
+    /// default hash.
+    ///
+    /// `seed` has the same meaning as in [`sample()`](crate::sample).
+    #[inline]
+    pub fn new(seed: u32, config: ScrambleConfig) -> CustomSampler {
+        CustomSampler::with_hash(seed, config)
+    }
+}
+
+impl<H: OwenHash> CustomSampler<H> {
+    /// Create a sampler for the given seed and configuration, using the
+    /// hash `H`.
+    ///
+    /// `seed` has the same meaning as in [`sample()`](crate::sample).
+    #[inline]
+    pub fn with_hash(seed: u32, config: ScrambleConfig) -> CustomSampler<H> {
+        CustomSampler {
+            config,
+            sampler: SeededSampler::new(seed),
+            hash: PhantomData,
+        }
+    }
+
+    /// The configuration of the sampler.
+    #[inline]
+    pub fn config(&self) -> ScrambleConfig {
+        self.config
+    }
+
+    /// Compute one dimension of a single sample in the Sobol sequence.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`sample()`](crate::sample).
+    #[inline]
+    pub fn sample(&self, sample_index: u32, dimension: u32) -> f32 {
+        debug_assert!(sample_index < (1 << 16));
+
+        let sobol = sobol_rev(self.shuffle_rev(sample_index), dimension);
+        let scramble = || hash(scramble_value(self.sampler.seed_mix, dimension));
+        let scrambled_rev = match self.config.scramble {
+            ScrambleMode::None => sobol,
+            ScrambleMode::DigitalShift => sobol ^ scramble(),
+            ScrambleMode::LinearMatousek => linear_scramble_rev(sobol, scramble()),
+            ScrambleMode::Owen => H::scramble_rev(sobol, scramble()),
+        };
+
+        u32_to_f32_norm(scrambled_rev.reverse_bits())
+    }
+
+    /// Compute four dimensions of a single sample in the Sobol sequence.
+    ///
+    /// Computes the same values as [`sample()`](CustomSampler::sample), but
+    /// four dimensions at once, the same as [`sample_4d()`](crate::sample_4d).
+    ///
+    /// # Panics
+    ///
+    /// Same as [`sample_4d()`](crate::sample_4d).
+    #[inline]
+    pub fn sample_4d(&self, sample_index: u32, dimension_set: u32) -> [f32; 4] {
+        debug_assert!(sample_index < (1 << 16));
+
+        let sobol = sobol_int4_rev(self.shuffle_rev(sample_index), dimension_set);
+        let scramble = || hash_int4(scramble_value_int4(self.sampler.seed_mix, dimension_set));
+        let scrambled_rev: Int4 = match self.config.scramble {
+            ScrambleMode::None => sobol,
+            ScrambleMode::DigitalShift => sobol ^ scramble(),
+            ScrambleMode::LinearMatousek => linear_scramble_int4_rev(sobol, scramble()),
+            ScrambleMode::Owen => H::scramble_int4_rev(sobol, scramble()),
+        };
+
+        // Un-reverse the bits and convert to floating point in [0, 1).
+        scrambled_rev.reverse_bits().to_f32_norm()
+    }
+
+    #[inline(always)]
+    fn shuffle_rev(&self, sample_index: u32) -> u32 {
+        if self.config.index_shuffle {
+            let scramble = self.sampler.index_scramble;
+            match self.config.sample_count {
+                Some(count) => {
+                    shuffle_index_with::<H>(sample_index, count, scramble).reverse_bits()
+                }
+                None => H::scramble_rev(sample_index.reverse_bits(), scramble),
+            }
+        } else {
+            sample_index.reverse_bits()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parts::{DoubleLkHash, ExactOwen, LkHash};
+    use crate::test_util::is_net;
+    use crate::{sample, sample_4d, NUM_DIMENSION_SETS_4D};
+
+    const CONFIGS: &[ScrambleConfig] = &[
+        ScrambleConfig::NONE,
+        ScrambleConfig::DIGITAL_SHIFT,
+        ScrambleConfig::INDEX_SHUFFLE_ONLY,
+        ScrambleConfig::LINEAR_MATOUSEK,
+        ScrambleConfig::OWEN,
+        ScrambleConfig {
+            index_shuffle: true,
+            scramble: ScrambleMode::LinearMatousek,
+            sample_count: None,
+        },
+    ];
+
+    #[test]
+    fn default_matches_sample() {
+        for s in 0..4 {
+            let sampler = CustomSampler::new(s, ScrambleConfig::default());
+            for d in 0..NUM_DIMENSION_SETS_4D {
+                for n in (0..(1 << 16)).step_by(257) {
+                    assert_eq!(sampler.sample_4d(n, d), sample_4d(n, d, s));
+                    assert_eq!(sampler.sample(n, d * 4 + 1), sample(n, d * 4 + 1, s));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn traits_without_hash_bounds() {
+        // A hash that implements none of the derivable traits.
+        struct PlainHash;
+        impl OwenHash for PlainHash {
+            fn scramble_rev(n_rev: u32, scramble: u32) -> u32 {
+                BetterLkHash::scramble_rev(n_rev, scramble)
+            }
+            fn scramble_int4_rev(n_rev: Int4, scramble: Int4) -> Int4 {
+                BetterLkHash::scramble_int4_rev(n_rev, scramble)
+            }
+        }
+
+        let a = CustomSampler::<PlainHash>::with_hash(3, ScrambleConfig::default());
+        let b = a;
+        #[allow(clippy::clone_on_copy)]
+        let c = a.clone();
+        assert!(a == b && b == c);
+        assert!(a != CustomSampler::<PlainHash>::with_hash(4, ScrambleConfig::default()));
+        assert_eq!(a.sample(5, 3), sample(5, 3, 3));
+    }
+
+    #[test]
+    fn check_1d_and_4d_match() {
+        check_1d_and_4d_match_with::<BetterLkHash>();
+        check_1d_and_4d_match_with::<LkHash>();
+        check_1d_and_4d_match_with::<DoubleLkHash>();
+        check_1d_and_4d_match_with::<ExactOwen>();
+    }
+
+    fn check_1d_and_4d_match_with<H: OwenHash>() {
+        for &config in CONFIGS {
+            for s in 0..4 {
+                let sampler = CustomSampler::<H>::with_hash(s, config);
+                for d in 0..8 {
+                    for n in 0..64 {
+                        let a = [
+                            sampler.sample(n, d * 4),
+                            sampler.sample(n, d * 4 + 1),
+                            sampler.sample(n, d * 4 + 2),
+                            sampler.sample(n, d * 4 + 3),
+                        ];
+                        assert_eq!(a, sampler.sample_4d(n, d));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn all_modes_stratified() {
+        all_modes_stratified_with::<BetterLkHash>();
+        all_modes_stratified_with::<LkHash>();
+        all_modes_stratified_with::<DoubleLkHash>();
+        all_modes_stratified_with::<ExactOwen>();
+    }
+
+    fn all_modes_stratified_with<H: OwenHash>() {
+        for &config in CONFIGS {
+            for s in 0..4 {
+                let sampler = CustomSampler::<H>::with_hash(s, config);
+                for d in 0..8 {
+                    let mut points = [[0.0f32]; 256];
+                    for (n, p) in points.iter_mut().enumerate() {
+                        *p = [sampler.sample(n as u32, d)];
+                    }
+                    assert!(is_net(&points));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sample_count_stratified() {
+        sample_count_stratified_with::<BetterLkHash>();
+        sample_count_stratified_with::<LkHash>();
+        sample_count_stratified_with::<DoubleLkHash>();
+        sample_count_stratified_with::<ExactOwen>();
+    }
+
+    fn sample_count_stratified_with<H: OwenHash>() {
+        for &count in [1, 3, 100, 200, 256].iter() {
+            let config = ScrambleConfig {
+                sample_count: Some(count),
+                ..ScrambleConfig::OWEN
+            };
+            for s in 0..4 {
+                let sampler = CustomSampler::<H>::with_hash(s, config);
+                for d in 0..8 {
+                    // The samples are the first `count` points of the
+                    // sequence, so at every power-of-two resolution up to
+                    // `count` each stratum gets the same number of samples,
+                    // give or take one.
+                    let mut k = 0;
+                    while (1 << k) <= count {
+                        let mut hits = [0u32; 256];
+                        for n in 0..count {
+                            let cell = (sampler.sample(n, d) * (1 << k) as f32) as usize;
+                            hits[cell] += 1;
+                        }
+                        for &h in hits[..(1 << k)].iter() {
+                            assert!(h == count >> k || h == (count >> k) + 1);
+                        }
+                        k += 1;
+                    }
+
+                    let n = count - 1;
+                    let a = [
+                        sampler.sample(n, d * 4),
+                        sampler.sample(n, d * 4 + 1),
+                        sampler.sample(n, d * 4 + 2),
+                        sampler.sample(n, d * 4 + 3),
+                    ];
+                    assert_eq!(a, sampler.sample_4d(n, d));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sample_count_matches_sample_counted() {
+        let config = ScrambleConfig {
+            sample_count: Some(100),
+            ..ScrambleConfig::OWEN
+        };
+        for s in 0..4 {
+            let sampler = CustomSampler::new(s, config);
+            for n in 0..100 {
+                assert_eq!(sampler.sample(n, 5), crate::sample_counted(n, 5, s, 100));
+                assert_eq!(
+                    sampler.sample_4d(n, 3),
+                    crate::sample_4d_counted(n, 3, s, 100)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn none_is_plain_sobol() {
+        let a = CustomSampler::new(1, ScrambleConfig::NONE);
+        let b = CustomSampler::new(2, ScrambleConfig::NONE);
+        for n in 0..64 {
+            assert_eq!(a.sample(n, 0), n.reverse_bits() as f32 / 4294967296.0);
+            assert_eq!(a.sample_4d(n, 3), b.sample_4d(n, 3));
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Sampling by domain key instead of by dimension number.
+
+use crate::{parts::hash, SampleContext, NUM_DIMENSIONS};
+
+/// A key identifying an independent 2d sampling domain.
+///
+/// A domain maps a key (such as `"camera.lens"`) to a pair of dimensions and
+/// a seed offset, derived from a hash of the key.  This lets independent
+/// parts of a program draw samples without having to coordinate which
+/// dimension numbers they use: each domain is stratified within itself, and
+/// is only randomly associated with other domains.
+///
+/// `Domain::new()` is a `const fn`, so keys can be hashed at compile time:
+///
+/// ```rust
+/// # use sobol_burley::{sample_domain, Domain};
+/// const LENS: Domain = Domain::new("camera.lens");
+///
+/// let [u, v] = sample_domain(0, LENS, 42);
+/// ```
+///
+/// Enum keys can be used via [`from_u32()`](Domain::from_u32):
+///
+/// ```rust
+/// # use sobol_burley::{sample_domain, Domain};
+/// enum Dom {
+///     Lens,
+///     Light,
+/// }
+///
+/// let light = Domain::from_u32(Dom::Light as u32);
+/// let [u, v] = sample_domain(0, light, 42);
+/// ```
+///
+/// Note that since domains are assigned dimensions by hashing, two
+/// different keys may end up with the same dimensions.  This is harmless:
+/// the seed offsets of the two domains still differ, so their samples are
+/// only randomly associated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Domain {
+    /// The first of the domain's two dimensions.  Always even, so that both
+    /// dimensions are in the same 4d dimension set.
+    dimension: u32,
+
+    /// Hashed together with the sequence seed.
+    seed: u32,
+}
+
+impl Domain {
+    /// Create a domain from a string key.
+    #[inline]
+    pub const fn new(key: &str) -> Domain {
+        Domain::from_bytes(key.as_bytes())
+    }
+
+    /// Create a domain from a byte-string key.
+    ///
+    /// `Domain::new(s)` and `Domain::from_bytes(s.as_bytes())` are the same
+    /// domain.
+    pub const fn from_bytes(key: &[u8]) -> Domain {
+        // Hash four bytes at a time, with the remainder zero-padded.
+        let mut h = hash(key.len() as u32 ^ 0x5e1f0a3b);
+        let mut i = 0;
+        while i < key.len() {
+            let mut chunk = 0u32;
+            let mut j = 0;
+            while j < 4 && (i + j) < key.len() {
+                chunk |= (key[i + j] as u32) << (j * 8);
+                j += 1;
+            }
+            h = hash(h ^ chunk);
+            i += 4;
+        }
+
+        Domain::from_hash(h)
+    }
+
+    /// Create a domain from an integer key.
+    ///
+    /// This is intended for keys from enums and the like.  Integer keys and
+    /// string keys are hashed differently, so there is no relationship
+    /// between e.g. `Domain::from_u32(1)` and `Domain::new("1")`.
+    #[inline]
+    pub const fn from_u32(key: u32) -> Domain {
+        Domain::from_hash(hash(key ^ 0x8d0b2c67))
+    }
+
+    /// The dimensions of the domain.
+    #[inline]
+    pub const fn dimensions(&self) -> [u32; 2] {
+        [self.dimension, self.dimension + 1]
+    }
+
+    #[inline]
+    const fn from_hash(h: u32) -> Domain {
+        Domain {
+            dimension: (h % (NUM_DIMENSIONS / 2)) * 2,
+            seed: hash(h ^ 0x3b6e9f21),
+        }
+    }
+}
+
+impl From<&str> for Domain {
+    #[inline]
+    fn from(key: &str) -> Domain {
+        Domain::new(key)
+    }
+}
+
+/// Compute a 2d sample in the given domain.
+///
+/// `sample_index` and `seed` have the same meaning as in
+/// [`sample()`](crate::sample).
+///
+/// `domain` identifies an independent sampling domain.  See [`Domain`] for
+/// details.  String keys can be passed directly, but they're then hashed at
+/// run time.  Prefer a `const` [`Domain`] in performance sensitive code.
+///
+/// ```rust
+/// # use sobol_burley::sample_domain;
+/// for i in 0..64 {
+///     let [u, v] = sample_domain(i, "camera.lens", 42);
+///     assert!(u < 1.0 && v < 1.0);
+/// }
+/// ```
+///
+/// # Panics
+///
+/// In debug, panics if `sample_index` is greater than or equal to 2^16.
+/// In release, returns unspecified floats in the interval [0, 1).
+#[inline]
+pub fn sample_domain<D: Into<Domain>>(sample_index: u32, domain: D, seed: u32) -> [f32; 2] {
+    let domain = domain.into();
+
+    // The seed is hashed before combining it with the domain's seed, so that
+    // incrementing seeds (e.g. per pixel) in one domain don't line up with
+    // the seeds of another domain.
+    let ctx = SampleContext::new(sample_index, hash(hash(seed ^ 0x6f3c1d85) ^ domain.seed));
+
+    [ctx.dim(domain.dimension), ctx.dim(domain.dimension + 1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::is_net;
+
+    #[test]
+    fn const_matches_runtime() {
+        const LENS: Domain = Domain::new("camera.lens");
+        let name = "camera.lens";
+        assert_eq!(LENS, Domain::from(name));
+        assert_eq!(LENS, Domain::from_bytes(name.as_bytes()));
+        assert_ne!(LENS, Domain::new("camera.lenz"));
+        assert_ne!(LENS, Domain::new("camera.lens\0"));
+        assert_ne!(Domain::from_u32(0), Domain::from_u32(1));
+    }
+
+    #[test]
+    fn stratified_within_domain() {
+        for seed in 0..4 {
+            for name in ["camera.lens", "light.select", "bsdf.0"] {
+                let mut us = [[0.0f32]; 256];
+                let mut vs = [[0.0f32]; 256];
+                for i in 0..256 {
+                    let [u, v] = sample_domain(i as u32, name, seed);
+                    us[i] = [u];
+                    vs[i] = [v];
+                }
+                assert!(is_net(&us) && is_net(&vs));
+            }
+        }
+    }
+
+    #[test]
+    fn domains_differ() {
+        let a = sample_domain(3, "camera.lens", 0);
+        let b = sample_domain(3, "camera.time", 0);
+        let c = sample_domain(3, "camera.lens", 1);
+        assert_ne!(a, b);
+        assert_ne!(a, c);
+    }
+}
@@ -0,0 +1,31 @@
+//! Error types.
+
+/// An error from one of the checked sampling functions, such as
+/// [`try_sample()`](crate::try_sample).
+///
+/// Implements `std::error::Error` when the `std` feature is enabled.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SampleError {
+    /// The sample index is greater than or equal to the maximum sequence
+    /// length of 2^16.
+    IndexOutOfRange,
+
+    /// The dimension is greater than or equal to
+    /// [`NUM_DIMENSIONS`](crate::NUM_DIMENSIONS), or the dimension set is
+    /// greater than or equal to
+    /// [`NUM_DIMENSION_SETS_4D`](crate::NUM_DIMENSION_SETS_4D).
+    DimensionOutOfRange,
+}
+
+impl core::fmt::Display for SampleError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            SampleError::IndexOutOfRange => f.write_str("sample index out of range"),
+            SampleError::DimensionOutOfRange => f.write_str("dimension out of range"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SampleError {}
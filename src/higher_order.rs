@@ -0,0 +1,154 @@
+//! Higher-order digital nets via digit interlacing.
+
+use crate::parts::{hash, owen_scramble_rev, sobol_rev, u64_to_f64_norm};
+use crate::seeded::scramble_value;
+use crate::{SeededSampler, NUM_DIMENSIONS};
+
+/// Compute one dimension of a single sample of a higher-order digital
+/// sequence, with interlacing factor `alpha`.
+///
+/// Following "Walsh Spaces Containing Smooth Functions and Quasi-Monte Carlo
+/// Rules of Arbitrary High Order" by Josef Dick, dimension `dimension` is
+/// made by interlacing the digits of the `alpha` consecutive Sobol
+/// dimensions starting at `dimension * alpha`: the first digit of each of
+/// them in turn, then the second digit of each, and so on.  For integrands
+/// that are smooth enough, the error of the resulting samples converges at
+/// a rate of up to O(N^-(`alpha` + 0.5)) instead of the O(N^-1.5) of
+/// [`sample()`](crate::sample).  The price is that each dimension uses up
+/// `alpha` Sobol dimensions, and that the benefit fades quickly for
+/// integrands that aren't smooth (e.g. that have discontinuities, as is
+/// common in rendering).
+///
+/// The Sobol dimensions are index shuffled and Owen scrambled exactly as in
+/// [`sample()`](crate::sample) before interlacing, which is the
+/// randomization from "Higher Order Scrambled Digital Nets Achieve the
+/// Optimal Rate of the Root Mean Square Error for Smooth Integrands", also
+/// by Dick.  With `alpha` of 1 this computes the same values as
+/// [`sample()`](crate::sample), but with more precision.
+///
+/// Interlacing needs `alpha` times as many digits as the underlying
+/// dimensions have, so the result is an `f64`.
+///
+/// ```rust
+/// # use sobol_burley::sample_higher_order;
+/// // Estimate the integral of x^2 over [0, 1), which is 1/3.
+/// let n = 256;
+/// let sum: f64 = (0..n)
+///     .map(|i| sample_higher_order(i, 0, 2, 42).powi(2))
+///     .sum();
+/// assert!((sum / n as f64 - 1.0 / 3.0).abs() < 1e-5);
+/// ```
+///
+/// # Panics
+///
+/// * Panics if `alpha` is zero or greater than 4, or if
+///   `(dimension + 1) * alpha` is greater than [`NUM_DIMENSIONS`].
+/// * In debug, panics if `sample_index` is greater than or equal to 2^16.
+///   In release, returns unspecified floats in the interval [0, 1).
+#[inline]
+pub fn sample_higher_order(sample_index: u32, dimension: u32, alpha: u32, seed: u32) -> f64 {
+    assert!((1..=4).contains(&alpha));
+    assert!((dimension as u64 + 1) * alpha as u64 <= NUM_DIMENSIONS as u64);
+    debug_assert!(sample_index < (1 << 16));
+
+    let sampler = SeededSampler::new(seed);
+    let shuffled_rev = owen_scramble_rev(sample_index.reverse_bits(), sampler.index_scramble);
+
+    // Interlace the digits, most significant first.  Each dimension only has
+    // 32 digits, so with `alpha` of 1 the lowest 32 bits are left empty.
+    let digits = (64 / alpha).min(32);
+    let mut n = 0u64;
+    for k in 0..alpha {
+        let d = dimension * alpha + k;
+        let sobol = sobol_rev(shuffled_rev, d);
+        let x = owen_scramble_rev(sobol, hash(scramble_value(sampler.seed_mix, d))).reverse_bits();
+        for j in 0..digits {
+            let digit = ((x >> (31 - j)) & 1) as u64;
+            n |= digit << (63 - (j * alpha + k));
+        }
+    }
+
+    u64_to_f64_norm(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sample;
+
+    /// Compute the root mean square error, over `seeds` seeds, of estimating
+    /// the integral of `f` over the unit square with `n` samples.
+    fn rmse_2d(f: fn(f64, f64) -> f64, exact: f64, n: u32, alpha: u32, seeds: u32) -> f64 {
+        let mut sum_sq = 0.0;
+        for s in 0..seeds {
+            let mut sum = 0.0;
+            for i in 0..n {
+                let x = sample_higher_order(i, 0, alpha, s);
+                let y = sample_higher_order(i, 1, alpha, s);
+                sum += f(x, y);
+            }
+            let err = sum / n as f64 - exact;
+            sum_sq += err * err;
+        }
+        (sum_sq / seeds as f64).sqrt()
+    }
+
+    #[test]
+    fn alpha_1_matches_sample() {
+        for s in 0..4 {
+            for i in 0..256 {
+                for d in 0..16 {
+                    let x = sample_higher_order(i, d, 1, s);
+                    let expected = sample(i, d, s);
+                    assert_eq!(
+                        (x * (1u64 << 23) as f64) as u32,
+                        (expected * (1 << 23) as f32) as u32
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn in_range() {
+        for alpha in 1..=4 {
+            for d in 0..(NUM_DIMENSIONS / alpha) {
+                for i in [0, 1, 1000, 0xffff] {
+                    let x = sample_higher_order(i, d, alpha, 7);
+                    assert!((0.0..1.0).contains(&x));
+                }
+            }
+        }
+    }
+
+    /// Check that `f`, with the known integral `exact`, converges faster with
+    /// interlacing.
+    fn check_convergence(f: fn(f64, f64) -> f64, exact: f64) {
+        // The error should drop by up to 2^(alpha + 0.5) for each doubling of
+        // the sample count.  Over 4 doublings that's 2^6 for alpha 1 and 2^10
+        // for alpha 2, though at these sample counts alpha 2 only gets to
+        // around 2^9.
+        let a1 = [
+            rmse_2d(f, exact, 1 << 6, 1, 32),
+            rmse_2d(f, exact, 1 << 10, 1, 32),
+        ];
+        let a2 = [
+            rmse_2d(f, exact, 1 << 6, 2, 32),
+            rmse_2d(f, exact, 1 << 10, 2, 32),
+        ];
+        assert!(a1[0] / a1[1] > 16.0);
+        assert!(a1[0] / a1[1] < 256.0);
+        assert!(a2[0] / a2[1] > 256.0);
+        assert!(a2[0] / a2[1] > (a1[0] / a1[1]) * 4.0);
+
+        // And it's more accurate at the same sample count.
+        assert!(a2[1] * 4.0 < a1[1]);
+    }
+
+    #[test]
+    fn higher_order_convergence() {
+        // Smooth polynomial integrands with known integrals.
+        check_convergence(|x, y| x * x * y * y * y, 1.0 / 12.0);
+        check_convergence(|x, y| (1.0 + x * y) * (1.0 + x * y), 1.0 + 0.5 + 1.0 / 9.0);
+    }
+}
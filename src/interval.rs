@@ -0,0 +1,157 @@
+//! Enumerating the samples that fall in a given 2D stratum.
+
+use crate::parts::{hash, owen_scramble_inverse_rev, owen_scramble_rev, sobol_rev};
+use crate::seeded::scramble_value;
+use crate::SeededSampler;
+
+/// Find the sample index of the `j`th sample whose first two dimensions
+/// fall in the given 2D stratum.
+///
+/// The stratum is the square (`x`, `y`) of a 2^`log2_resolution` by
+/// 2^`log2_resolution` grid over the unit square.  For example, with the
+/// grid as the pixels of an image, this finds the samples of the sequence
+/// that land in a given pixel.  It follows "Enumerating Quasi-Monte Carlo
+/// Point Sequences in Elementary Intervals" by Leonhard Grünschloß, Matthias
+/// Raab, and Alexander Keller, extended to undo this crate's index shuffling
+/// and Owen scrambling.
+///
+/// Every aligned block of 2^(2 * `log2_resolution`) samples has exactly
+/// one sample in each stratum, and `j` selects the block.  So the returned
+/// indices increase with `j`, and enumerating `j` from zero finds every
+/// sample of the stratum in order:
+///
+/// ```rust
+/// # use sobol_burley::{index_in_interval, sample};
+/// // The first four samples in pixel (5, 2) of a 16x16 grid.
+/// for j in 0..4 {
+///     let i = index_in_interval(5, 2, 4, j, 42);
+///     assert_eq!((sample(i, 0, 42) * 16.0) as u32, 5);
+///     assert_eq!((sample(i, 1, 42) * 16.0) as u32, 2);
+/// }
+/// ```
+///
+/// # Panics
+///
+/// In debug, panics if `log2_resolution` is greater than 8, if `x` or `y`
+/// is outside of the grid, or if `j` is greater than or equal to
+/// 2^(16 - 2 * `log2_resolution`), the number of samples in each stratum.
+#[inline]
+pub fn index_in_interval(x: u32, y: u32, log2_resolution: u32, j: u32, seed: u32) -> u32 {
+    let r = log2_resolution;
+    debug_assert!(r <= 8);
+    debug_assert!(x < (1 << r) && y < (1 << r));
+    debug_assert!(j < (1 << (16 - 2 * r)));
+
+    let sampler = SeededSampler::new(seed);
+    let mask_r = ((1u64 << r) - 1) as u32;
+
+    // The shuffled index of block `j` has the same high bits for every
+    // sample in the block, since the shuffle is an Owen scramble.
+    let block_rev = owen_scramble_rev((j << (2 * r)).reverse_bits(), sampler.index_scramble);
+    let high = (block_rev.reverse_bits() & 0xffff) >> (2 * r) << (2 * r);
+
+    // Un-scramble the first `r` digits of the target coordinates.  In
+    // reversed bits, those are the lowest `r` bits.
+    let unscramble = |coordinate: u32, dimension: u32| {
+        let target_rev = (coordinate.reverse_bits() >> (32 - r.max(1))) & mask_r;
+        let scramble = hash(scramble_value(sampler.seed_mix, dimension));
+        owen_scramble_inverse_rev(target_rev, scramble) & mask_r
+    };
+    let (u0, u1) = (unscramble(x, 0), unscramble(y, 1));
+
+    // Solve for the low `2r` bits of the shuffled index.  Sobol generation is
+    // linear over GF(2), so the first `r` digits of both dimensions are the
+    // XOR of the contributions of each index bit.  The equations are packed
+    // as the digits of dimension 0 in the low `r` bits and of dimension 1 in
+    // the next `r` bits.
+    let digits = |index_rev: u32| {
+        (sobol_rev(index_rev, 0) & mask_r) | ((sobol_rev(index_rev, 1) & mask_r) << r)
+    };
+    let high_rev = high.reverse_bits();
+    let target = (u0 | (u1 << r)) ^ digits(high_rev);
+
+    // Gaussian elimination, tracking which index bits make up each basis
+    // vector.  Dimensions 0 and 1 are a (0,2)-sequence, so the system always
+    // has exactly one solution.
+    let mut basis = [(0u32, 0u32); 16];
+    for k in 0..(2 * r) {
+        let (mut v, mut bits) = (digits(1 << (31 - k)), 1u32 << k);
+        while v != 0 {
+            let lead = 31 - v.leading_zeros() as usize;
+            if basis[lead].0 == 0 {
+                basis[lead] = (v, bits);
+                break;
+            }
+            v ^= basis[lead].0;
+            bits ^= basis[lead].1;
+        }
+    }
+    let (mut t, mut low) = (target, 0u32);
+    while t != 0 {
+        let lead = 31 - t.leading_zeros() as usize;
+        t ^= basis[lead].0;
+        low ^= basis[lead].1;
+    }
+
+    // Invert the index shuffle.  The reversed-bit shuffled index's low bits
+    // (the high bits of the index) must be whatever the shuffle produces for
+    // an index below 2^16.
+    let shuffled_rev =
+        (high | low).reverse_bits() | (owen_scramble_rev(0, sampler.index_scramble) & 0xffff);
+    owen_scramble_inverse_rev(shuffled_rev, sampler.index_scramble).reverse_bits()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sample;
+
+    #[test]
+    fn finds_samples_in_stratum() {
+        for s in 0..4 {
+            for r in 0..=3 {
+                let res = 1 << r;
+                for y in 0..res {
+                    for x in 0..res {
+                        let mut prev = None;
+                        for j in (0..(1 << (16 - 2 * r))).step_by(97) {
+                            let i = index_in_interval(x, y, r, j, s);
+                            assert!(i < (1 << 16));
+                            assert_eq!((sample(i, 0, s) * res as f32) as u32, x);
+                            assert_eq!((sample(i, 1, s) * res as f32) as u32, y);
+                            assert_eq!(i >> (2 * r), j);
+                            if let Some(p) = prev {
+                                assert!(i > p);
+                            }
+                            prev = Some(i);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn enumerates_all_samples() {
+        // Every sample is found exactly once.
+        let r = 2;
+        let mut found = [false; 1 << 12];
+        for y in 0..4 {
+            for x in 0..4 {
+                for j in 0..(1 << 8) {
+                    let i = index_in_interval(x, y, r, j, 7) as usize;
+                    assert!(!found[i]);
+                    found[i] = true;
+                }
+            }
+        }
+        assert!(found.iter().all(|&f| f));
+
+        // Including at the finest resolution.
+        for y in 0..256 {
+            let i = index_in_interval(3, y, 8, 0, 7);
+            assert_eq!((sample(i, 0, 7) * 256.0) as u32, 3);
+            assert_eq!((sample(i, 1, 7) * 256.0) as u32, y);
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Iterators over the points of a Sobol sequence.
+
+use core::iter::FusedIterator;
+
+use crate::{SeededSampler, NUM_DIMENSIONS, NUM_DIMENSION_SETS_4D};
+
+/// The maximum sequence length.
+const SEQUENCE_LEN: u32 = 1 << 16;
+
+/// Create an iterator over the points of the Sobol sequence with the given
+/// `seed`.
+///
+/// Each point is an array containing the dimensions listed in `dimensions`,
+/// in the same order.  The iterator yields the points in sample index order,
+/// starting at zero, and ends after the maximum sequence length of 2^16.
+/// Use iterator adapters like `take()` to get fewer points.
+///
+/// The points are identical to computing each dimension with
+/// [`sample()`](crate::sample):
+///
+/// ```rust
+/// # use sobol_burley::{points, sample};
+/// for (i, [x, y, z]) in points(42, [0, 1, 7]).take(100).enumerate() {
+///     let i = i as u32;
+///     assert_eq!([x, y, z], [sample(i, 0, 42), sample(i, 1, 42), sample(i, 7, 42)]);
+/// }
+/// ```
+///
+/// Skipping ahead (e.g. with `nth()` or `skip()`) is O(1), as is iterating
+/// from the back.
+///
+/// # Panics
+///
+/// Panics if any of the `dimensions` is greater than or equal to
+/// [`NUM_DIMENSIONS`].
+#[inline]
+pub fn points<const N: usize>(seed: u32, dimensions: [u32; N]) -> Points<N> {
+    for &d in dimensions.iter() {
+        assert!(d < NUM_DIMENSIONS);
+    }
+
+    Points {
+        sampler: SeededSampler::new(seed),
+        dimensions,
+        front: 0,
+        back: SEQUENCE_LEN,
+    }
+}
+
+/// Create an iterator over four dimensions of the points of the Sobol
+/// sequence with the given `seed`.
+///
+/// Same as [`points()`], except the points are computed with
+/// [`sample_4d()`](crate::sample_4d).  `dimension_set` is interpreted the same
+/// as in [`sample_4d()`](crate::sample_4d).
+///
+/// # Panics
+///
+/// Panics if `dimension_set` is greater than or equal to
+/// [`NUM_DIMENSION_SETS_4D`].
+#[inline]
+pub fn points_4d(seed: u32, dimension_set: u32) -> Points4d {
+    assert!(dimension_set < NUM_DIMENSION_SETS_4D);
+
+    Points4d {
+        sampler: SeededSampler::new(seed),
+        dimension_set,
+        front: 0,
+        back: SEQUENCE_LEN,
+    }
+}
+
+/// An iterator over the points of a Sobol sequence.
+///
+/// Created by [`points()`].
+#[derive(Debug, Clone)]
+pub struct Points<const N: usize> {
+    sampler: SeededSampler,
+    dimensions: [u32; N],
+    front: u32,
+    back: u32,
+}
+
+impl<const N: usize> Points<N> {
+    #[inline(always)]
+    fn point(&self, sample_index: u32) -> [f32; N] {
+        let ctx = self.sampler.context(sample_index);
+        let mut point = [0.0f32; N];
+        for (n, &d) in point.iter_mut().zip(self.dimensions.iter()) {
+            *n = ctx.dim(d);
+        }
+        point
+    }
+}
+
+impl<const N: usize> Iterator for Points<N> {
+    type Item = [f32; N];
+
+    #[inline]
+    fn next(&mut self) -> Option<[f32; N]> {
+        self.nth(0)
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<[f32; N]> {
+        let len = (self.back - self.front) as usize;
+        if n >= len {
+            self.front = self.back;
+            return None;
+        }
+        let i = self.front + n as u32;
+        self.front = i + 1;
+        Some(self.point(i))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.back - self.front) as usize;
+        (len, Some(len))
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<[f32; N]> {
+        self.next_back()
+    }
+}
+
+impl<const N: usize> DoubleEndedIterator for Points<N> {
+    #[inline]
+    fn next_back(&mut self) -> Option<[f32; N]> {
+        self.nth_back(0)
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<[f32; N]> {
+        let len = (self.back - self.front) as usize;
+        if n >= len {
+            self.back = self.front;
+            return None;
+        }
+        self.back -= n as u32 + 1;
+        Some(self.point(self.back))
+    }
+}
+
+impl<const N: usize> ExactSizeIterator for Points<N> {}
+impl<const N: usize> FusedIterator for Points<N> {}
+
+/// An iterator over four dimensions of the points of a Sobol sequence.
+///
+/// Created by [`points_4d()`].
+#[derive(Debug, Clone)]
+pub struct Points4d {
+    sampler: SeededSampler,
+    dimension_set: u32,
+    front: u32,
+    back: u32,
+}
+
+impl Iterator for Points4d {
+    type Item = [f32; 4];
+
+    #[inline]
+    fn next(&mut self) -> Option<[f32; 4]> {
+        self.nth(0)
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<[f32; 4]> {
+        let len = (self.back - self.front) as usize;
+        if n >= len {
+            self.front = self.back;
+            return None;
+        }
+        let i = self.front + n as u32;
+        self.front = i + 1;
+        Some(self.sampler.sample_4d(i, self.dimension_set))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.back - self.front) as usize;
+        (len, Some(len))
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<[f32; 4]> {
+        self.next_back()
+    }
+}
+
+impl DoubleEndedIterator for Points4d {
+    #[inline]
+    fn next_back(&mut self) -> Option<[f32; 4]> {
+        self.nth_back(0)
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<[f32; 4]> {
+        let len = (self.back - self.front) as usize;
+        if n >= len {
+            self.back = self.front;
+            return None;
+        }
+        self.back -= n as u32 + 1;
+        Some(self.sampler.sample_4d(self.back, self.dimension_set))
+    }
+}
+
+impl ExactSizeIterator for Points4d {}
+impl FusedIterator for Points4d {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sample, sample_4d};
+
+    #[test]
+    fn matches_sample() {
+        for (i, p) in points(5, [3, 0, 200]).enumerate() {
+            let i = i as u32;
+            assert_eq!(p, [sample(i, 3, 5), sample(i, 0, 5), sample(i, 200, 5)]);
+        }
+        for (i, p) in points_4d(5, 10).enumerate() {
+            assert_eq!(p, sample_4d(i as u32, 10, 5));
+        }
+    }
+
+    #[test]
+    fn len_and_skipping() {
+        let mut it = points(1, [0, 1]);
+        assert_eq!(it.len(), 1 << 16);
+
+        assert_eq!(it.nth(1000), Some([sample(1000, 0, 1), sample(1000, 1, 1)]));
+        assert_eq!(it.len(), (1 << 16) - 1001);
+
+        assert_eq!(
+            it.next_back(),
+            Some([sample(65535, 0, 1), sample(65535, 1, 1)])
+        );
+        assert_eq!(
+            it.nth_back(9),
+            Some([sample(65525, 0, 1), sample(65525, 1, 1)])
+        );
+        assert_eq!(it.len(), 65525 - 1001);
+
+        let mut it = points_4d(1, 0).skip(65530);
+        assert_eq!(it.next(), Some(sample_4d(65530, 0, 1)));
+        assert_eq!(it.len(), 5);
+        assert_eq!(it.nth(5), None);
+        assert_eq!(it.next(), None);
+
+        let mut it = points_4d(1, 0);
+        assert_eq!(it.nth(1 << 16), None);
+        assert_eq!(it.next_back(), None);
+    }
+}
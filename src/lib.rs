@@ -13,7 +13,8 @@
 //! * The maximum sequence length is 2^16.
 //! * The maximum number of dimensions is 256 (although this can be worked
 //!   around with seeding).
-//! * Only `f32` output is supported.
+//! * Output is `f32`, except for the `f64` of [`sample_higher_order()`],
+//!   which needs the extra precision.
 //!
 //! These are all trade-offs for the sake of better performance and a smaller
 //! memory footprint.
@@ -42,6 +43,15 @@
 //! If all you want is a single Owen-scrambled Sobol sequence, then this is
 //! all you need.  You can ignore the third parameter.
 //!
+//! Alternatively, [`points()`] provides the same thing as an iterator:
+//!
+//! ```rust
+//! # use sobol_burley::points;
+//! for [x, y, z] in points(0, [0, 1, 2]).take(1024) {
+//!     println!("({}, {}, {})", x, y, z);
+//! }
+//! ```
+//!
 //!
 //! ## Seeding
 //!
@@ -57,6 +67,12 @@
 //! related integral estimates.  For example, in a 3d renderer you might
 //! pass a different seed to each pixel so that error in the pixel colors
 //! shows up as noise instead of as structured artifacts.
+//! Alternatively, [`ZSampler`] spreads a single sequence across all the
+//! pixels of an image, and [`sample_blue_noise()`] uses per-pixel seeds
+//! optimized offline, both of which distribute the error as blue noise.
+//!
+//! When seeds are built from many inputs (e.g. frame, tile, and pixel), the
+//! [`Seed`] type can help avoid accidental collisions between them.
 //!
 //! Another important application is "padding" the dimensions of a Sobol
 //! sequence.  By changing the seed we can re-use the same dimensions over
@@ -80,6 +96,17 @@
 //! See Burley's paper for justification of this padding approach as well as
 //! recommendations about its use.
 //!
+//! [`sample_unbounded()`] and [`sample_4d_unbounded()`] do this
+//! automatically for dimensions beyond [`NUM_DIMENSIONS`], and [`Sampler`]
+//! does the same while handing out the dimensions of a sample one after
+//! another.  [`sample_temporal()`] similarly re-seeds to continue a sequence
+//! past 2^16 samples, for spreading one sequence over the frames of an
+//! animation, and [`split_sample()`] does the same for splitting a path
+//! into several child paths.
+//! Alternatively, [`sample_domain()`] lets independent parts of a program
+//! draw samples by name (e.g. `"camera.lens"`) instead of by dimension
+//! number.
+//!
 //!
 //! # SIMD
 //!
@@ -108,15 +135,58 @@
 //! ```
 //!
 //! The difference is only in performance and how the dimensions are indexed.
+//!
+//!
+//! # Re-using a seed
+//!
+//! If you take many samples with the same seed (for example, all the samples
+//! of a single pixel), [`SeededSampler`] pre-computes the parts of `sample()`
+//! and `sample_4d()` that only depend on the seed.  It produces identical
+//! results, just a bit faster.
 
 #![no_std]
 #![allow(clippy::unreadable_literal)]
 #![allow(clippy::needless_range_loop)]
 
+#[cfg(feature = "std")]
+extern crate std;
+
+mod blue_noise;
+mod custom;
+mod domain;
+mod error;
+#[cfg(test)]
+mod golden;
+mod higher_order;
+mod interval;
+mod iter;
+mod net;
 pub mod parts;
+mod pmj;
+mod sampler;
+mod seed;
+mod seeded;
+#[cfg(test)]
+mod test_util;
 mod wide;
+mod zorder;
+
+pub use blue_noise::{sample_4d_blue_noise, sample_blue_noise, BlueNoiseOptimizer, BlueNoiseTiles};
+pub use custom::{CustomSampler, ScrambleConfig, ScrambleMode};
+pub use domain::{sample_domain, Domain};
+pub use error::SampleError;
+pub use higher_order::sample_higher_order;
+pub use interval::index_in_interval;
+pub use iter::{points, points_4d, Points, Points4d};
+pub use net::{sample_net, sample_net_4d};
+pub use pmj::{pmj02, pmj02_2d};
+pub use sampler::Sampler;
+pub use seed::Seed;
+pub use seeded::{SampleContext, SeededSampler};
+pub use zorder::ZSampler;
 
-// This `include` provides `NUM_DIMENSIONS` and `REV_VECTORS`.
+// This `include` provides `NUM_DIMENSIONS`, `REV_VECTORS`, `NUM_PAIRS_2D`,
+// `PAIRS_2D`, and `PAIRS_2D_T_VALUES`.
 // See the build.rs file for how this included file is generated.
 include!(concat!(env!("OUT_DIR"), "/vectors.inc"));
 
@@ -145,29 +215,7 @@ pub const NUM_DIMENSION_SETS_4D: u32 = NUM_DIMENSIONS / 4;
 ///   In release, returns unspecified floats in the interval [0, 1).
 #[inline]
 pub fn sample(sample_index: u32, dimension: u32, seed: u32) -> f32 {
-    use parts::*;
-    debug_assert!(sample_index < (1 << 16));
-
-    // Shuffle the index using the given seed to produce a unique statistically
-    // independent Sobol sequence.
-    let shuffled_rev_index =
-        owen_scramble_rev(sample_index.reverse_bits(), hash(seed ^ 0x79c68e4a));
-
-    let sobol = sobol_rev(shuffled_rev_index, dimension);
-
-    // Compute the scramble value for doing Owen scrambling.
-    // The multiply on `seed` is to avoid accidental cancellation
-    // with `dimension` on an incrementing or otherwise structured
-    // seed.
-    let scramble = {
-        let seed = seed.wrapping_mul(0x9c8f2d3b);
-        let ds = dimension >> 2;
-        ds ^ seed ^ [0x912f69ba, 0x174f18ab, 0x691e72ca, 0xb40cc1b8][dimension as usize & 0b11]
-    };
-
-    let sobol_owen_rev = owen_scramble_rev(sobol, hash(scramble));
-
-    u32_to_f32_norm(sobol_owen_rev.reverse_bits())
+    SeededSampler::new(seed).sample(sample_index, dimension)
 }
 
 /// Compute four dimensions of a single sample in the Sobol sequence.
@@ -188,30 +236,555 @@ pub fn sample(sample_index: u32, dimension: u32, seed: u32) -> f32 {
 ///   In release, returns unspecified floats in the interval [0, 1).
 #[inline]
 pub fn sample_4d(sample_index: u32, dimension_set: u32, seed: u32) -> [f32; 4] {
-    use parts::*;
-    debug_assert!(sample_index < (1 << 16));
+    SeededSampler::new(seed).sample_4d(sample_index, dimension_set)
+}
+
+/// Same as [`sample()`], but returns an error instead of panicking or
+/// returning unspecified values.
+///
+/// ```rust
+/// # use sobol_burley::{sample, try_sample, SampleError, NUM_DIMENSIONS};
+/// assert_eq!(try_sample(5, 3, 42), Ok(sample(5, 3, 42)));
+/// assert_eq!(try_sample(1 << 16, 3, 42), Err(SampleError::IndexOutOfRange));
+/// assert_eq!(try_sample(5, NUM_DIMENSIONS, 42), Err(SampleError::DimensionOutOfRange));
+/// ```
+///
+/// # Errors
+///
+/// * [`SampleError::IndexOutOfRange`] if `sample_index` is greater than or
+///   equal to 2^16.
+/// * [`SampleError::DimensionOutOfRange`] if `dimension` is greater than or
+///   equal to [`NUM_DIMENSIONS`].
+#[inline]
+pub fn try_sample(sample_index: u32, dimension: u32, seed: u32) -> Result<f32, SampleError> {
+    if sample_index >= (1 << 16) {
+        return Err(SampleError::IndexOutOfRange);
+    }
+    if dimension >= NUM_DIMENSIONS {
+        return Err(SampleError::DimensionOutOfRange);
+    }
+
+    Ok(unsafe { sample_unchecked(sample_index, dimension, seed) })
+}
+
+/// Same as [`sample_4d()`], but returns an error instead of panicking or
+/// returning unspecified values.
+///
+/// # Errors
+///
+/// * [`SampleError::IndexOutOfRange`] if `sample_index` is greater than or
+///   equal to 2^16.
+/// * [`SampleError::DimensionOutOfRange`] if `dimension_set` is greater than
+///   or equal to [`NUM_DIMENSION_SETS_4D`].
+#[inline]
+pub fn try_sample_4d(
+    sample_index: u32,
+    dimension_set: u32,
+    seed: u32,
+) -> Result<[f32; 4], SampleError> {
+    if sample_index >= (1 << 16) {
+        return Err(SampleError::IndexOutOfRange);
+    }
+    if dimension_set >= NUM_DIMENSION_SETS_4D {
+        return Err(SampleError::DimensionOutOfRange);
+    }
+
+    Ok(unsafe { sample_4d_unchecked(sample_index, dimension_set, seed) })
+}
+
+/// Same as [`sample()`], but without any checks in release builds.
+///
+/// That leaves no panic paths at all, which can be useful in hot loops
+/// where the parameters are already known to be valid.
+///
+/// # Safety
+///
+/// `dimension` must be less than [`NUM_DIMENSIONS`].  Passing a larger
+/// dimension is undefined behavior, which debug builds catch with a panic.
+///
+/// `sample_index` is not checked even in debug, but passing a sample index
+/// greater than or equal to 2^16 is safe: it just returns unspecified floats
+/// in the interval [0, 1).
+#[inline]
+pub unsafe fn sample_unchecked(sample_index: u32, dimension: u32, seed: u32) -> f32 {
+    SeededSampler::new(seed)
+        .context_unchecked(sample_index)
+        .dim_unchecked(dimension)
+}
+
+/// Same as [`sample_4d()`], but without any checks in release builds.
+///
+/// That leaves no panic paths at all, which can be useful in hot loops
+/// where the parameters are already known to be valid.
+///
+/// # Safety
+///
+/// `dimension_set` must be less than [`NUM_DIMENSION_SETS_4D`].  Passing a
+/// larger dimension set is undefined behavior, which debug builds catch
+/// with a panic.
+///
+/// `sample_index` is not checked even in debug, but passing a sample index
+/// greater than or equal to 2^16 is safe: it just returns unspecified floats
+/// in the interval [0, 1).
+#[inline]
+pub unsafe fn sample_4d_unchecked(sample_index: u32, dimension_set: u32, seed: u32) -> [f32; 4] {
+    SeededSampler::new(seed)
+        .context_unchecked(sample_index)
+        .dims_4d_unchecked(dimension_set)
+}
+
+/// Compute four arbitrary dimensions of a single sample in the Sobol
+/// sequence.
+///
+/// This is identical to calling [`sample()`] for each of the four
+/// `dimensions`, but computes them at once with SIMD, the same as
+/// [`sample_4d()`].  Unlike [`sample_4d()`], the dimensions don't need to
+/// be an aligned dimension set:
+///
+/// ```rust
+/// # use sobol_burley::{sample, sample_4d_dims};
+/// let a = sample_4d_dims(0, [0, 5, 17, 200], 42);
+/// let b = [sample(0, 0, 42), sample(0, 5, 42), sample(0, 17, 42), sample(0, 200, 42)];
+/// assert_eq!(a, b);
+/// ```
+///
+/// Aligned dimension sets are just as fast as with [`sample_4d()`].  Other
+/// dimensions are somewhat slower, but still faster than computing them one
+/// at a time.
+///
+/// # Panics
+///
+/// * Panics if any of the `dimensions` is greater than or equal to
+///   [`NUM_DIMENSIONS`].
+/// * In debug, panics if `sample_index` is greater than or equal to 2^16.
+///   In release, returns unspecified floats in the interval [0, 1).
+#[inline]
+pub fn sample_4d_dims(sample_index: u32, dimensions: [u32; 4], seed: u32) -> [f32; 4] {
+    SeededSampler::new(seed).sample_4d_dims(sample_index, dimensions)
+}
+
+/// Compute `N` consecutive dimensions of a single sample in the Sobol
+/// sequence, starting at `first_dimension`.
+///
+/// This is identical to calling [`sample()`] for each dimension, but
+/// computes as many of the dimensions as possible with SIMD, the same as
+/// [`sample_4d()`].  `first_dimension` doesn't need to be a multiple of four.
+///
+/// ```rust
+/// # use sobol_burley::{sample, sample_nd};
+/// let [x, y, z] = sample_nd(0, 5, 42);
+/// assert_eq!([x, y, z], [sample(0, 5, 42), sample(0, 6, 42), sample(0, 7, 42)]);
+/// ```
+///
+/// # Panics
+///
+/// * Panics if `first_dimension + N` is greater than [`NUM_DIMENSIONS`].
+/// * In debug, panics if `sample_index` is greater than or equal to 2^16.
+///   In release, returns unspecified floats in the interval [0, 1).
+#[inline]
+pub fn sample_nd<const N: usize>(sample_index: u32, first_dimension: u32, seed: u32) -> [f32; N] {
+    SeededSampler::new(seed).sample_nd(sample_index, first_dimension)
+}
+
+/// Compute a 2D sample from one of the dimension pairs with the best 2D
+/// projections.
+///
+/// The 2D projections of some dimension pairs of the Sobol sequence are
+/// much better stratified than others, including within the same 4d
+/// dimension set.  At build time, every pair of dimensions is ranked by the
+/// t-value of its 2D projection over the full 2^16 samples, and the
+/// dimensions are greedily paired up, best first, into the disjoint pairs
+/// listed in [`PAIRS_2D`].  This computes pair `pair_index` of that table,
+/// so lower pair indices have better 2D stratification.
+///
+/// This is useful for choosing the dimensions of 2D domains like lens
+/// positions or pixel filter offsets:
+///
+/// ```rust
+/// # use sobol_burley::{sample, sample_2d, PAIRS_2D};
+/// let lens = sample_2d(5, 0, 42);
+/// let filter = sample_2d(5, 1, 42);
+///
+/// let [d1, d2] = PAIRS_2D[1];
+/// assert_eq!(filter, [sample(5, d1, 42), sample(5, d2, 42)]);
+/// ```
+///
+/// The pairs are disjoint, so different pair indices never share
+/// dimensions.  However, they do share dimensions with [`sample()`] and the
+/// other sampling functions, so mixing them for the same sample should be
+/// done with care.
+///
+/// # Panics
+///
+/// * Panics if `pair_index` is greater than or equal to [`NUM_PAIRS_2D`].
+/// * In debug, panics if `sample_index` is greater than or equal to 2^16.
+///   In release, returns unspecified floats in the interval [0, 1).
+#[inline]
+pub fn sample_2d(sample_index: u32, pair_index: u32, seed: u32) -> [f32; 2] {
+    let [d1, d2] = PAIRS_2D[pair_index as usize];
+    let ctx = SeededSampler::new(seed).context(sample_index);
+    [ctx.dim(d1), ctx.dim(d2)]
+}
+
+/// Same as [`sample()`], but only for sequences of up to
+/// 2^`log2_sample_count` samples.
+///
+/// When the total number of samples is known up front, only the first
+/// `log2_sample_count` depths of the Sobol sequence contribute to its
+/// stratification.  This skips the deeper ones, which makes it faster
+/// for smaller sample counts.  The bits below the stratified ones are filled
+/// with pseudo-random jitter by the Owen scrambling.
+///
+/// The first 2^`log2_sample_count` samples have the same stratification
+/// guarantees as [`sample()`], but are *not* the same values.  Mixing
+/// the two in the same sequence should be avoided.
+///
+/// # Panics
+///
+/// * Panics if `dimension` is greater than or equal to [`NUM_DIMENSIONS`].
+/// * In debug, panics if `log2_sample_count` is greater than 16 or if
+///   `sample_index` is greater than or equal to 2^`log2_sample_count`.
+#[inline]
+pub fn sample_limited(sample_index: u32, dimension: u32, seed: u32, log2_sample_count: u32) -> f32 {
+    SeededSampler::new(seed).sample_limited(sample_index, dimension, log2_sample_count)
+}
+
+/// Same as [`sample_4d()`], but only for sequences of up to
+/// 2^`log2_sample_count` samples.
+///
+/// See [`sample_limited()`] for details.
+///
+/// # Panics
+///
+/// * Panics if `dimension_set` is greater than or equal to
+///   [`NUM_DIMENSION_SETS_4D`].
+/// * In debug, panics if `log2_sample_count` is greater than 16 or if
+///   `sample_index` is greater than or equal to 2^`log2_sample_count`.
+#[inline]
+pub fn sample_4d_limited(
+    sample_index: u32,
+    dimension_set: u32,
+    seed: u32,
+    log2_sample_count: u32,
+) -> [f32; 4] {
+    SeededSampler::new(seed).sample_4d_limited(sample_index, dimension_set, log2_sample_count)
+}
+
+/// Same as [`sample()`], but for a sequence of exactly `sample_count`
+/// samples.
+///
+/// The index shuffle of [`sample()`] is an Owen scramble over all 2^16
+/// sample indices.  For a power-of-two number of samples that gives an
+/// aligned block of the sequence, but for other counts it gives a random
+/// subset of the next power of two.  This instead shuffles with
+/// [`parts::shuffle_index()`], a permutation of `0..sample_count`, so that
+/// the samples are exactly the first `sample_count` points of the
+/// sequence.  That keeps every power-of-two sized prefix of the sequence
+/// intact, which is the best stratification achievable for an arbitrary
+/// sample count:
+///
+/// ```rust
+/// # use sobol_burley::sample_counted;
+/// // With 100 samples, each 64th of [0, 1) gets either one or two of them.
+/// let mut hits = [0; 64];
+/// for i in 0..100 {
+///     hits[(sample_counted(i, 0, 42, 100) * 64.0) as usize] += 1;
+/// }
+/// assert!(hits.iter().all(|&h| h == 1 || h == 2));
+/// ```
+///
+/// The samples are *not* the same values as [`sample()`] produces, even for
+/// power-of-two counts.
+///
+/// # Panics
+///
+/// * Panics if `dimension` is greater than or equal to [`NUM_DIMENSIONS`],
+///   or if `sample_count` is zero.
+/// * In debug, panics if `sample_count` is greater than 2^16 or if
+///   `sample_index` is greater than or equal to `sample_count`.  In release,
+///   returns unspecified floats in the interval [0, 1).
+#[inline]
+pub fn sample_counted(sample_index: u32, dimension: u32, seed: u32, sample_count: u32) -> f32 {
+    SeededSampler::new(seed).sample_counted(sample_index, dimension, sample_count)
+}
+
+/// Same as [`sample_4d()`], but for a sequence of exactly `sample_count`
+/// samples.
+///
+/// See [`sample_counted()`] for details.
+///
+/// # Panics
+///
+/// * Panics if `dimension_set` is greater than or equal to
+///   [`NUM_DIMENSION_SETS_4D`], or if `sample_count` is zero.
+/// * In debug, panics if `sample_count` is greater than 2^16 or if
+///   `sample_index` is greater than or equal to `sample_count`.  In release,
+///   returns unspecified floats in the interval [0, 1).
+#[inline]
+pub fn sample_4d_counted(
+    sample_index: u32,
+    dimension_set: u32,
+    seed: u32,
+    sample_count: u32,
+) -> [f32; 4] {
+    SeededSampler::new(seed).sample_4d_counted(sample_index, dimension_set, sample_count)
+}
+
+/// Compute one dimension of a single sample in the Sobol sequence, with no
+/// limit on the dimension.
+///
+/// This is identical to [`sample()`], except that instead of panicking,
+/// dimensions greater than or equal to [`NUM_DIMENSIONS`] are padded by
+/// re-seeding, as described below.  Dimensions less than [`NUM_DIMENSIONS`]
+/// always produce the same values as [`sample()`]:
+///
+/// ```rust
+/// # use sobol_burley::{sample, sample_unbounded, NUM_DIMENSIONS};
+/// for d in 0..NUM_DIMENSIONS {
+///     assert_eq!(sample_unbounded(3, d as u64, 42), sample(3, d, 42));
+/// }
+/// let x = sample_unbounded(3, 1_000_000, 42);
+/// ```
+///
+/// # Padding
+///
+/// The dimensions are split into rounds of [`NUM_DIMENSIONS`] dimensions
+/// each.  `dimension` is computed as dimension
+/// `dimension % NUM_DIMENSIONS` of the sequence with the seed for round
+/// `dimension / NUM_DIMENSIONS`.  Round zero uses `seed` itself, and every
+/// other round uses a seed produced by hashing together `seed` and the
+/// round number.
+///
+/// This means that each round of dimensions is stratified within itself,
+/// but is only randomly associated with the other rounds.  It also means
+/// that the sequences of different seeds remain independent of each other
+/// in every round.  [`Sampler`] pads the same way.
+///
+/// # Panics
+///
+/// In debug, panics if `sample_index` is greater than or equal to 2^16.
+/// In release, returns unspecified floats in the interval [0, 1).
+#[inline]
+pub fn sample_unbounded(sample_index: u32, dimension: u64, seed: u32) -> f32 {
+    let round = dimension / NUM_DIMENSIONS as u64;
+    let dimension = (dimension % NUM_DIMENSIONS as u64) as u32;
+
+    sample(sample_index, dimension, padded_seed(seed, round))
+}
+
+/// Compute four dimensions of a single sample in the Sobol sequence, with no
+/// limit on the dimension set.
+///
+/// This is identical to [`sample_4d()`], except that instead of panicking,
+/// dimension sets greater than or equal to [`NUM_DIMENSION_SETS_4D`] are
+/// padded by re-seeding.  The padding is the same as in
+/// [`sample_unbounded()`], so the two always compute identical results.
+///
+/// # Panics
+///
+/// In debug, panics if `sample_index` is greater than or equal to 2^16.
+/// In release, returns unspecified floats in the interval [0, 1).
+#[inline]
+pub fn sample_4d_unbounded(sample_index: u32, dimension_set: u64, seed: u32) -> [f32; 4] {
+    let round = dimension_set / NUM_DIMENSION_SETS_4D as u64;
+    let dimension_set = (dimension_set % NUM_DIMENSION_SETS_4D as u64) as u32;
+
+    sample_4d(sample_index, dimension_set, padded_seed(seed, round))
+}
+
+/// Compute one dimension of a single sample for frame `frame` of an
+/// animation, with the frames sharing a single sequence.
+///
+/// Each frame takes 2^`log2_samples_per_frame` samples, indexed by
+/// `sample_in_frame`, and is assigned the next contiguous slice of the
+/// sequence with the given `seed`: frame `f` gets sample indices
+/// `(f << log2_samples_per_frame) | sample_in_frame`.  So the samples of
+/// any aligned run of 2^k frames (in particular, the first 2^k frames) are
+/// together stratified like 2^k times as many samples of a single frame,
+/// which temporal accumulation can take advantage of.  With a different
+/// seed per frame instead, each frame would only be stratified within
+/// itself.
+///
+/// Once the 2^16 sample limit of the sequence is reached, the sequence
+/// continues with a new seed derived from `seed`, similar to how
+/// [`sample_unbounded()`] pads dimensions but with seeds of its own, so
+/// the later frames are independent of the padded dimensions.  The first
+/// 2^16 samples use `seed` itself:
+///
+/// ```rust
+/// # use sobol_burley::{sample, sample_temporal};
+/// // Frame 3, sample 1 of 4 is sample 13 of the sequence.
+/// assert_eq!(sample_temporal(3, 1, 0, 42, 2), sample(13, 0, 42));
+///
+/// // Any frame number works.
+/// let x = sample_temporal(1_000_000, 1, 0, 42, 2);
+/// ```
+///
+/// # Panics
+///
+/// * Panics if `dimension` is greater than or equal to [`NUM_DIMENSIONS`].
+/// * In debug, panics if `log2_samples_per_frame` is greater than 16, or if
+///   `sample_in_frame` is greater than or equal to
+///   2^`log2_samples_per_frame`.
+#[inline]
+pub fn sample_temporal(
+    frame: u32,
+    sample_in_frame: u32,
+    dimension: u32,
+    seed: u32,
+    log2_samples_per_frame: u32,
+) -> f32 {
+    let (sample_index, round) = block_index(frame, sample_in_frame, log2_samples_per_frame);
+    sample(
+        sample_index,
+        dimension,
+        salted_padded_seed(seed, round, TEMPORAL_SALT),
+    )
+}
 
-    // Shuffle the index using the given seed to produce a unique statistically
-    // independent Sobol sequence.
-    let shuffled_rev_index =
-        owen_scramble_rev(sample_index.reverse_bits(), hash(seed ^ 0x79c68e4a));
+/// Same as [`sample_temporal()`], except computes four dimensions at once,
+/// the same as [`sample_4d()`].
+///
+/// # Panics
+///
+/// * Panics if `dimension_set` is greater than or equal to
+///   [`NUM_DIMENSION_SETS_4D`].
+/// * In debug, panics if `log2_samples_per_frame` is greater than 16, or if
+///   `sample_in_frame` is greater than or equal to
+///   2^`log2_samples_per_frame`.
+#[inline]
+pub fn sample_4d_temporal(
+    frame: u32,
+    sample_in_frame: u32,
+    dimension_set: u32,
+    seed: u32,
+    log2_samples_per_frame: u32,
+) -> [f32; 4] {
+    let (sample_index, round) = block_index(frame, sample_in_frame, log2_samples_per_frame);
+    sample_4d(
+        sample_index,
+        dimension_set,
+        salted_padded_seed(seed, round, TEMPORAL_SALT),
+    )
+}
 
-    let sobol = sobol_int4_rev(shuffled_rev_index, dimension_set);
+/// Compute one dimension of a single sample for child `child` of a split
+/// path, with the children sharing the parent's sequence.
+///
+/// When a path splits into 2^`log2_k` child paths at some vertex (for
+/// example, several light samples at one hit point), each child needs its
+/// own samples for the dimensions after the split.  Child `child` of the
+/// path with sample index `parent_index` uses sample index
+/// `(parent_index << log2_k) | child`, so the children of each parent are
+/// an aligned block of the sequence.  That makes the children of a single
+/// parent stratified among themselves, and the children of the first N
+/// parents together stratified like the first N * 2^`log2_k` samples of the
+/// sequence.
+///
+/// The children should use different dimensions than the parent's own
+/// samples at `parent_index`, typically the dimensions that come after the
+/// split.  Indices past the 2^16 sample limit continue with a new seed, as
+/// in [`sample_temporal()`] but with seeds of their own:
+///
+/// ```rust
+/// # use sobol_burley::{sample, split_sample};
+/// // Child 2 of 4 of path 3 is sample 14 of the sequence.
+/// assert_eq!(split_sample(3, 2, 2, 10, 42), sample(14, 10, 42));
+/// ```
+///
+/// # Panics
+///
+/// * Panics if `dimension` is greater than or equal to [`NUM_DIMENSIONS`].
+/// * In debug, panics if `log2_k` is greater than 16, or if `child` is
+///   greater than or equal to 2^`log2_k`.
+#[inline]
+pub fn split_sample(parent_index: u32, child: u32, log2_k: u32, dimension: u32, seed: u32) -> f32 {
+    let (sample_index, round) = block_index(parent_index, child, log2_k);
+    sample(
+        sample_index,
+        dimension,
+        salted_padded_seed(seed, round, SPLIT_SALT),
+    )
+}
+
+/// Same as [`split_sample()`], except computes four dimensions at once, the
+/// same as [`sample_4d()`].
+///
+/// # Panics
+///
+/// * Panics if `dimension_set` is greater than or equal to
+///   [`NUM_DIMENSION_SETS_4D`].
+/// * In debug, panics if `log2_k` is greater than 16, or if `child` is
+///   greater than or equal to 2^`log2_k`.
+#[inline]
+pub fn split_sample_4d(
+    parent_index: u32,
+    child: u32,
+    log2_k: u32,
+    dimension_set: u32,
+    seed: u32,
+) -> [f32; 4] {
+    let (sample_index, round) = block_index(parent_index, child, log2_k);
+    sample_4d(
+        sample_index,
+        dimension_set,
+        salted_padded_seed(seed, round, SPLIT_SALT),
+    )
+}
+
+/// Compute the sample index and re-seeding round for sample
+/// `index_in_block` of block `block`, where the sequence is split into
+/// blocks of 2^`log2_block_size` samples.
+///
+/// Used by [`sample_temporal()`] and [`split_sample()`].
+#[inline(always)]
+fn block_index(block: u32, index_in_block: u32, log2_block_size: u32) -> (u32, u64) {
+    debug_assert!(log2_block_size <= 16);
+    debug_assert!(index_in_block < (1 << log2_block_size));
+
+    let index = ((block as u64) << log2_block_size) | index_in_block as u64;
+    (index as u32 & 0xffff, index >> 16)
+}
+
+/// Salt for the re-seeding of [`sample_temporal()`].
+const TEMPORAL_SALT: u32 = 0x5c1f3a97;
+
+/// Salt for the re-seeding of [`split_sample()`].
+const SPLIT_SALT: u32 = 0x8e2b64d1;
+
+/// Compute the seed for the given round of dimension padding.
+///
+/// Round zero is `seed` itself, so that the first round of padded dimensions
+/// is identical to the un-padded dimensions.  Later rounds are hashed
+/// together with the seed so that they're decorrelated from each other and
+/// from the seeds of other sequences.
+#[inline(always)]
+pub(crate) fn padded_seed(seed: u32, round: u64) -> u32 {
+    use parts::hash;
 
-    // Compute the scramble values for doing Owen scrambling.
-    // The multiply on `seed` is to avoid accidental cancellation
-    // with `dimension` on an incrementing or otherwise structured
-    // seed.
-    let scramble = {
-        let seed: Int4 = [seed.wrapping_mul(0x9c8f2d3b); 4].into();
-        let ds: Int4 = [dimension_set; 4].into();
-        seed ^ ds ^ [0x912f69ba, 0x174f18ab, 0x691e72ca, 0xb40cc1b8].into()
-    };
+    if round == 0 {
+        seed
+    } else {
+        hash(hash(hash(seed ^ 0x2a4d5f0e) ^ round as u32) ^ (round >> 32) as u32)
+    }
+}
 
-    let sobol_owen_rev = owen_scramble_int4_rev(sobol, hash_int4(scramble));
+/// Same as `padded_seed()`, but for re-seeding other than dimension
+/// padding.
+///
+/// Each use of re-seeding passes its own `salt`, so that e.g. the later
+/// rounds of [`sample_temporal()`] aren't the same sequences as the padded
+/// dimensions of [`sample_unbounded()`] with the same seed.  Round zero is
+/// still `seed` itself.
+#[inline(always)]
+pub(crate) fn salted_padded_seed(seed: u32, round: u64, salt: u32) -> u32 {
+    use parts::hash;
 
-    // Un-reverse the bits and convert to floating point in [0, 1).
-    sobol_owen_rev.reverse_bits().to_f32_norm()
+    if round == 0 {
+        seed
+    } else {
+        padded_seed(hash(seed ^ salt), round)
+    }
 }
 
 //----------------------------------------------------------------
@@ -219,6 +792,7 @@ pub fn sample_4d(sample_index: u32, dimension_set: u32, seed: u32) -> [f32; 4] {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::is_net;
 
     #[test]
     fn check_1d_and_4d_match() {
@@ -240,4 +814,285 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn check_1d_and_4d_limited_match() {
+        for s in 0..4 {
+            for d in 0..8 {
+                for n in 0..256 {
+                    let a = [
+                        sample_limited(n, d * 4, s, 8),
+                        sample_limited(n, d * 4 + 1, s, 8),
+                        sample_limited(n, d * 4 + 2, s, 8),
+                        sample_limited(n, d * 4 + 3, s, 8),
+                    ];
+                    assert_eq!(a, sample_4d_limited(n, d, s, 8));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn limited_full_depth_matches() {
+        for s in 0..4 {
+            for d in 0..8 {
+                for n in (0..(1 << 16)).step_by(111) {
+                    assert_eq!(sample_limited(n, d, s, 16), sample(n, d, s));
+                    assert_eq!(sample_4d_limited(n, d, s, 16), sample_4d(n, d, s));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn limited_is_stratified() {
+        // The first 2^k samples should have the same stratification as with
+        // `sample()`: each dimension is stratified on its own, and the first
+        // two dimensions form a (0,k,2)-net.
+        const K: u32 = 8;
+        for s in 0..4 {
+            for d in 0..16 {
+                let mut points = [[0.0f32]; 1 << K];
+                for (n, p) in points.iter_mut().enumerate() {
+                    *p = [sample_limited(n as u32, d, s, K)];
+                }
+                assert!(is_net(&points));
+            }
+
+            let mut points = [[0.0f32; 2]; 1 << K];
+            for (n, p) in points.iter_mut().enumerate() {
+                let n = n as u32;
+                *p = [sample_limited(n, 0, s, K), sample_limited(n, 1, s, K)];
+            }
+            assert!(is_net(&points));
+        }
+    }
+
+    #[test]
+    fn check_1d_and_nd_match() {
+        fn check<const N: usize>(n: u32, first_dimension: u32, s: u32) {
+            let a: [f32; N] = sample_nd(n, first_dimension, s);
+            for i in 0..N {
+                assert_eq!(a[i], sample(n, first_dimension + i as u32, s));
+            }
+        }
+
+        for s in 0..4 {
+            for d in 0..8 {
+                for n in 0..64 {
+                    check::<0>(n, d, s);
+                    check::<1>(n, d, s);
+                    check::<2>(n, d, s);
+                    check::<3>(n, d, s);
+                    check::<5>(n, d, s);
+                    check::<7>(n, d, s);
+                    check::<13>(n, d, s);
+                }
+            }
+        }
+        check::<256>(5, 0, 1);
+        check::<3>(5, 253, 1);
+    }
+
+    #[test]
+    fn check_1d_and_4d_dims_match() {
+        for s in 0..4 {
+            for d in 0..64 {
+                for n in 0..64 {
+                    let dims = [d, (d * 7 + 3) % NUM_DIMENSIONS, d + 1, 255 - d];
+                    let a = sample_4d_dims(n, dims, s);
+                    for i in 0..4 {
+                        assert_eq!(a[i], sample(n, dims[i], s));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn check_1d_and_4d_unbounded_match() {
+        for s in 0..4 {
+            for ds in [0u64, 1, 63, 64, 65, 1000, 1 << 40, u64::MAX / 4] {
+                for n in 0..64 {
+                    let a = [
+                        sample_unbounded(n, ds * 4, s),
+                        sample_unbounded(n, ds * 4 + 1, s),
+                        sample_unbounded(n, ds * 4 + 2, s),
+                        sample_unbounded(n, ds * 4 + 3, s),
+                    ];
+                    assert_eq!(a, sample_4d_unbounded(n, ds, s));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unbounded_padding() {
+        for s in 0..4 {
+            for d in 0..NUM_DIMENSIONS {
+                assert_eq!(sample_unbounded(7, d as u64, s), sample(7, d, s));
+            }
+
+            // Every round gets a distinct seed.
+            let rounds = [0u64, 1, 2, 1 << 32, (1 << 32) + 1, u64::MAX / 256];
+            for (i, &a) in rounds.iter().enumerate() {
+                for &b in rounds[(i + 1)..].iter() {
+                    assert_ne!(padded_seed(s, a), padded_seed(s, b));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn checked_and_unchecked_match() {
+        for s in 0..4 {
+            for d in 0..NUM_DIMENSION_SETS_4D {
+                for n in (0..(1 << 16)).step_by(257) {
+                    let a = sample_4d(n, d, s);
+                    assert_eq!(try_sample_4d(n, d, s), Ok(a));
+                    assert_eq!(unsafe { sample_4d_unchecked(n, d, s) }, a);
+                    for i in 0..4 {
+                        assert_eq!(try_sample(n, d * 4 + i, s), Ok(a[i as usize]));
+                        assert_eq!(unsafe { sample_unchecked(n, d * 4 + i, s) }, a[i as usize]);
+                    }
+                }
+            }
+        }
+
+        assert_eq!(
+            try_sample_4d(1 << 16, 0, 0),
+            Err(SampleError::IndexOutOfRange)
+        );
+        assert_eq!(
+            try_sample_4d(0, NUM_DIMENSION_SETS_4D, 0),
+            Err(SampleError::DimensionOutOfRange)
+        );
+        assert_eq!(
+            try_sample(u32::MAX, u32::MAX, 0),
+            Err(SampleError::IndexOutOfRange)
+        );
+    }
+
+    #[test]
+    fn temporal() {
+        for s in 0..4 {
+            // Frames are consecutive slices of the sequence.
+            for frame in 0..64 {
+                for i in 0..4 {
+                    let n = frame * 4 + i;
+                    assert_eq!(sample_temporal(frame, i, 5, s, 2), sample(n, 5, s));
+                    assert_eq!(sample_4d_temporal(frame, i, 7, s, 2), sample_4d(n, 7, s));
+                }
+            }
+
+            // Past the end of the sequence, it continues with a new seed.
+            let a = sample_4d_temporal(1 << 14, 0, 0, s, 2);
+            assert_eq!(a, sample_4d(0, 0, salted_padded_seed(s, 1, TEMPORAL_SALT)));
+            assert_ne!(a, sample_4d(0, 0, s));
+            let b = sample_temporal(!0, 1, 1, s, 16);
+            let seed = salted_padded_seed(s, (!0u32) as u64, TEMPORAL_SALT);
+            assert_eq!(b, sample(1, 1, seed));
+
+            // And that seed isn't the one of any padded dimensions.
+            for d in 0..8 {
+                let n = sample_temporal(1 << 14, 0, d, s, 2);
+                assert_ne!(n, sample_unbounded(0, NUM_DIMENSIONS as u64 + d as u64, s));
+            }
+        }
+    }
+
+    #[test]
+    fn split() {
+        for s in 0..4 {
+            for parent in 0..64 {
+                for child in 0..8 {
+                    let n = parent * 8 + child;
+                    assert_eq!(split_sample(parent, child, 3, 9, s), sample(n, 9, s));
+                    assert_eq!(split_sample_4d(parent, child, 3, 2, s), sample_4d(n, 2, s));
+                }
+            }
+
+            // Past the end of the sequence, it continues with a new seed that
+            // differs from both the padded dimensions and the later frames of
+            // `sample_temporal()`.
+            let a = split_sample(1 << 12, 3, 4, 6, s);
+            assert_eq!(a, sample(3, 6, salted_padded_seed(s, 1, SPLIT_SALT)));
+            assert_ne!(a, sample_unbounded(3, NUM_DIMENSIONS as u64 + 6, s));
+            assert_ne!(a, sample_temporal(1 << 12, 3, 6, s, 4));
+
+            // The children of each parent are stratified among themselves.
+            for parent in [0, 5, 1000] {
+                for d in 0..8 {
+                    let mut points = [[0.0f32]; 16];
+                    for (child, p) in points.iter_mut().enumerate() {
+                        *p = [split_sample(parent, child as u32, 4, d, s)];
+                    }
+                    assert!(is_net(&points));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pairs_2d() {
+        // Every dimension is in exactly one pair.
+        let mut used = [false; NUM_DIMENSIONS as usize];
+        for pair in PAIRS_2D.iter() {
+            for &d in pair.iter() {
+                assert!(!used[d as usize]);
+                used[d as usize] = true;
+            }
+        }
+        assert_eq!(PAIRS_2D[0], [0, 1]);
+        assert_eq!(PAIRS_2D_T_VALUES[0], 0);
+        for i in 0..64 {
+            let [d1, d2] = PAIRS_2D[7];
+            assert_eq!(sample_2d(i, 7, 3), [sample(i, d1, 3), sample(i, d2, 3)]);
+        }
+    }
+
+    #[test]
+    fn pairs_2d_t_values() {
+        // Check the build-time t-values against the actual points, for a
+        // few of the pairs.
+        const M: u32 = 16;
+        let mut xs = [0u16; 1 << M];
+        let mut ys = [0u16; 1 << M];
+        let mut counts = [0u32; 1 << M];
+        for &p in [0u32, 1, NUM_PAIRS_2D / 2, NUM_PAIRS_2D - 1].iter() {
+            let t = PAIRS_2D_T_VALUES[p as usize];
+            for i in 0..(1 << M) {
+                let [x, y] = sample_2d(i, p, 5);
+                xs[i as usize] = (x * (1 << M) as f32) as u16;
+                ys[i as usize] = (y * (1 << M) as f32) as u16;
+            }
+
+            // Each elementary interval of area 2^(t-m) holds 2^t points.
+            let k = M - t;
+            for a in 0..=k {
+                counts.iter_mut().for_each(|c| *c = 0);
+                for (&x, &y) in xs.iter().zip(ys.iter()) {
+                    let cx = x as usize >> (M - a);
+                    let cy = y as usize >> (M - (k - a));
+                    counts[(cy << a) | cx] += 1;
+                }
+                assert!(counts[..(1 << k)].iter().all(|&c| c == 1 << t));
+            }
+
+            // And the t-value is tight.
+            if t > 0 {
+                let k = k + 1;
+                let tight = (0..=k).any(|a| {
+                    counts.iter_mut().for_each(|c| *c = 0);
+                    for (&x, &y) in xs.iter().zip(ys.iter()) {
+                        let cx = x as usize >> (M - a);
+                        let cy = y as usize >> (M - (k - a));
+                        counts[(cy << a) | cx] += 1;
+                    }
+                    counts[..(1 << k)].iter().any(|&c| c != 1 << (t - 1))
+                });
+                assert!(tight);
+            }
+        }
+    }
 }
@@ -0,0 +1,160 @@
+//! Finite Hammersley-style nets.
+
+use crate::parts::{
+    hash, hash_int4, owen_scramble_int4_rev, owen_scramble_rev, sobol_int4_dims_rev,
+    sobol_limited_rev, u32_to_f32_norm,
+};
+use crate::seeded::{scramble_value, scramble_value_int4};
+use crate::{SeededSampler, NUM_DIMENSIONS, NUM_DIMENSION_SETS_4D};
+
+/// Compute one dimension of a single point of a net of 2^`log2_n` points.
+///
+/// When the total number of points N = 2^`log2_n` is fixed up front, the
+/// fraction `i / N` can be used as an extra dimension alongside the Sobol
+/// sequence, as in the Hammersley construction.  Dimension 0 is that
+/// fraction (Owen scrambled), and dimension `d` is dimension `d - 1` of the
+/// Sobol sequence.  This gives one more dimension of perfect stratification
+/// than the sequence: the first three dimensions together form a
+/// (0,m,3)-net, so they're stratified in every set of N equal-volume boxes
+/// of power-of-two dimensions, where the sequence only manages that for
+/// its first two dimensions.
+///
+/// `seed` works the same as in [`sample()`](crate::sample), and the points
+/// are index shuffled and Owen scrambled the same way.
+///
+/// ```rust
+/// # use sobol_burley::sample_net;
+/// // 16 points, exactly one in each of 16 slices of the unit interval.
+/// let mut hits = [false; 16];
+/// for i in 0..16 {
+///     let x = sample_net(i, 4, 0, 42);
+///     hits[(x * 16.0) as usize] = true;
+/// }
+/// assert!(hits.iter().all(|&h| h));
+/// ```
+///
+/// # When to use
+///
+/// Unlike with [`sample()`](crate::sample), only the full set of N points
+/// is well distributed: a prefix of the points isn't generally stratified
+/// in dimension 0, and the points can't be extended with more points later.
+/// So this is better than the sequence when the point count is known and
+/// all of the points are always used, such as with a fixed number of
+/// samples per pixel, and a three dimensional integrand (e.g. 2D pixel area
+/// plus time) benefits from the extra stratification.  For adaptive or
+/// progressive sampling, use the sequence instead.
+///
+/// # Panics
+///
+/// * Panics if `dimension` is greater than or equal to [`NUM_DIMENSIONS`].
+/// * In debug, panics if `log2_n` is greater than 16 or if `sample_index`
+///   is greater than or equal to 2^`log2_n`.
+#[inline]
+pub fn sample_net(sample_index: u32, log2_n: u32, dimension: u32, seed: u32) -> f32 {
+    assert!(dimension < NUM_DIMENSIONS);
+    let sampler = SeededSampler::new(seed);
+    let net_rev = net_index_rev(&sampler, sample_index, log2_n);
+
+    let n_rev = if dimension == 0 {
+        fraction_rev(net_rev, log2_n)
+    } else {
+        sobol_limited_rev(net_rev, dimension - 1, log2_n)
+    };
+    let owen_rev = owen_scramble_rev(n_rev, hash(scramble_value(sampler.seed_mix, dimension)));
+
+    u32_to_f32_norm(owen_rev.reverse_bits())
+}
+
+/// Same as [`sample_net()`], except computes four dimensions at once, the
+/// same as [`sample_4d()`](crate::sample_4d).
+///
+/// # Panics
+///
+/// * Panics if `dimension_set` is greater than or equal to
+///   [`NUM_DIMENSION_SETS_4D`].
+/// * In debug, panics if `log2_n` is greater than 16 or if `sample_index`
+///   is greater than or equal to 2^`log2_n`.
+#[inline]
+pub fn sample_net_4d(sample_index: u32, log2_n: u32, dimension_set: u32, seed: u32) -> [f32; 4] {
+    assert!(dimension_set < NUM_DIMENSION_SETS_4D);
+    let sampler = SeededSampler::new(seed);
+    let net_rev = net_index_rev(&sampler, sample_index, log2_n);
+
+    // Every dimension shifts down by one onto the Sobol dimensions, so the
+    // direction vectors have to be gathered.  In the first set, the first
+    // lane is the fraction dimension instead.
+    let d = dimension_set * 4;
+    let mut n_rev = sobol_int4_dims_rev(net_rev, [d.saturating_sub(1), d, d + 1, d + 2]);
+    if dimension_set == 0 {
+        let mut lanes: [u32; 4] = n_rev.into();
+        lanes[0] = fraction_rev(net_rev, log2_n);
+        n_rev = lanes.into();
+    }
+    let owen_rev = owen_scramble_int4_rev(
+        n_rev,
+        hash_int4(scramble_value_int4(sampler.seed_mix, dimension_set)),
+    );
+
+    owen_rev.reverse_bits().to_f32_norm()
+}
+
+/// Compute the reversed-bit index into the net, with index shuffling.
+///
+/// Only the top `log2_n` bits are set.  The index shuffle maps the first
+/// 2^`log2_n` indices to an aligned block of shuffled indices, so their low
+/// `log2_n` bits (the top bits when reversed) are a permutation of the
+/// net's points.
+#[inline(always)]
+fn net_index_rev(sampler: &SeededSampler, sample_index: u32, log2_n: u32) -> u32 {
+    debug_assert!(log2_n <= 16);
+    debug_assert!(sample_index < (1 << log2_n));
+
+    let mask = !(((1u64 << (32 - log2_n)) - 1) as u32);
+    owen_scramble_rev(sample_index.reverse_bits(), sampler.index_scramble) & mask
+}
+
+/// Compute `i / N` with reversed bits, from the reversed-bit net index.
+#[inline(always)]
+fn fraction_rev(net_rev: u32, log2_n: u32) -> u32 {
+    ((net_rev as u64) >> (32 - log2_n)) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::is_net;
+
+    #[test]
+    fn check_1d_and_4d_match() {
+        for s in 0..4 {
+            for m in [0, 3, 8, 16] {
+                for i in (0..(1u32 << m)).step_by(37) {
+                    for ds in [0, 1, 5, NUM_DIMENSION_SETS_4D - 1] {
+                        let a = [
+                            sample_net(i, m, ds * 4, s),
+                            sample_net(i, m, ds * 4 + 1, s),
+                            sample_net(i, m, ds * 4 + 2, s),
+                            sample_net(i, m, ds * 4 + 3, s),
+                        ];
+                        assert_eq!(a, sample_net_4d(i, m, ds, s));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn first_three_dimensions_are_a_net() {
+        for s in 0..4 {
+            for m in 0..=8 {
+                let n = 1usize << m;
+                let mut points = [[0.0f32; 3]; 256];
+                for (i, p) in points[..n].iter_mut().enumerate() {
+                    let [x, y, z, _] = sample_net_4d(i as u32, m, 0, s);
+                    *p = [x, y, z];
+                }
+                assert!(is_net(&points[..n]));
+            }
+        }
+    }
+}
@@ -92,6 +92,193 @@ pub fn sobol_int4_rev(sample_index_rev: u32, dimension_set: u32) -> Int4 {
     sobol
 }
 
+/// Same as [`sobol_rev()`], but without bounds checking `dimension`.
+///
+/// # Safety
+///
+/// `dimension` must be less than [`NUM_DIMENSIONS`].  Passing a larger
+/// dimension is undefined behavior, which debug builds catch with a panic.
+#[inline]
+pub unsafe fn sobol_rev_unchecked(sample_index_rev: u32, dimension: u32) -> u32 {
+    debug_assert!(dimension < NUM_DIMENSIONS);
+
+    // The direction vectors are organized for SIMD, so we
+    // need to access them this way.
+    let dimension_set = (dimension >> 2) as usize;
+    let sub_dimension = (dimension & 0b11) as usize;
+
+    // Compute the Sobol sample with reversed bits.
+    let vecs = REV_VECTORS.get_unchecked(dimension_set);
+    let mut sobol = 0u32;
+    for i in 16..32 {
+        let mask = 0u32.wrapping_sub((sample_index_rev >> i) & 1);
+        sobol ^= mask & vecs[i - (32 - vecs.len())][sub_dimension];
+    }
+
+    sobol
+}
+
+/// Same as [`sobol_int4_rev()`], but without bounds checking
+/// `dimension_set`.
+///
+/// # Safety
+///
+/// `dimension_set` must be less than [`NUM_DIMENSION_SETS_4D`].  Passing a
+/// larger dimension set is undefined behavior, which debug builds catch
+/// with a panic.
+#[inline]
+pub unsafe fn sobol_int4_rev_unchecked(sample_index_rev: u32, dimension_set: u32) -> Int4 {
+    debug_assert!(dimension_set < NUM_DIMENSION_SETS_4D);
+
+    // Compute the Sobol sample with reversed bits.
+    let vecs = REV_VECTORS.get_unchecked(dimension_set as usize);
+    let mut sobol = Int4::zero();
+
+    let index_rev = Int4::splat(sample_index_rev);
+    const ZERO: Int4 = Int4::zero();
+    const ONE: Int4 = Int4::one();
+    for i in 16..32 {
+        let mask = ZERO - ((index_rev >> i as i32) & ONE);
+        sobol ^= mask & vecs[i - (32 - vecs.len())].into();
+    }
+
+    sobol
+}
+
+/// Same as [`sobol_rev()`] except returns four arbitrary dimensions at once.
+///
+/// Unlike [`sobol_int4_rev()`], the dimensions don't need to be an aligned
+/// dimension set.  Lane `i` of the result is dimension `dimensions[i]`.
+///
+/// When `dimensions` happens to be an aligned dimension set this is just as
+/// fast as [`sobol_int4_rev()`].  Otherwise the direction vectors are
+/// gathered from the table lane by lane, which is somewhat slower.
+///
+/// # Panics
+///
+/// Panics if any of the `dimensions` is greater than or equal to
+/// [`NUM_DIMENSIONS`].
+#[inline]
+pub fn sobol_int4_dims_rev(sample_index_rev: u32, dimensions: [u32; 4]) -> Int4 {
+    for &d in dimensions.iter() {
+        assert!(d < NUM_DIMENSIONS);
+    }
+
+    // Fast path for aligned dimension sets.
+    let dimension_set = dimensions[0] >> 2;
+    if (dimensions[0] & 0b11) == 0
+        && dimensions[1] == dimensions[0] + 1
+        && dimensions[2] == dimensions[0] + 2
+        && dimensions[3] == dimensions[0] + 3
+    {
+        return sobol_int4_rev(sample_index_rev, dimension_set);
+    }
+
+    // The direction vectors are organized for SIMD, so we
+    // need to access them this way.
+    let vecs = [
+        &REV_VECTORS[(dimensions[0] >> 2) as usize],
+        &REV_VECTORS[(dimensions[1] >> 2) as usize],
+        &REV_VECTORS[(dimensions[2] >> 2) as usize],
+        &REV_VECTORS[(dimensions[3] >> 2) as usize],
+    ];
+    let sub = [
+        (dimensions[0] & 0b11) as usize,
+        (dimensions[1] & 0b11) as usize,
+        (dimensions[2] & 0b11) as usize,
+        (dimensions[3] & 0b11) as usize,
+    ];
+
+    // Compute the Sobol sample with reversed bits.
+    let mut sobol = Int4::zero();
+    let index_rev = Int4::splat(sample_index_rev);
+    const ZERO: Int4 = Int4::zero();
+    const ONE: Int4 = Int4::one();
+    for i in 16..32 {
+        let j = i - (32 - vecs[0].len());
+        let v: Int4 = [
+            vecs[0][j][sub[0]],
+            vecs[1][j][sub[1]],
+            vecs[2][j][sub[2]],
+            vecs[3][j][sub[3]],
+        ]
+        .into();
+        let mask = ZERO - ((index_rev >> i as i32) & ONE);
+        sobol ^= mask & v;
+    }
+
+    sobol
+}
+
+/// Same as [`sobol_rev()`], except only computes the depths needed for the
+/// first 2^`log2_sample_count` samples.
+///
+/// For a sequence of 2^k samples only the first k depths of the Sobol
+/// sequence contribute stratification.  This skips the deeper ones, and
+/// therefore only produces the top `log2_sample_count` bits of the result
+/// (i.e. the lowest bits of the reversed-bit result), with the remaining bits
+/// set to zero.  Scrambling the result with [`owen_scramble_rev()`] fills
+/// those bits with pseudo-random jitter.
+///
+/// Only the bits of `sample_index_rev` that correspond to the first
+/// 2^`log2_sample_count` samples are used.  Higher sample index bits are
+/// ignored, which only amounts to a digital shift of the sequence.
+///
+/// # Panics
+///
+/// * Panics if `dimension` is greater than or equal to [`NUM_DIMENSIONS`].
+/// * In debug, panics if `log2_sample_count` is greater than 16.
+#[inline]
+pub fn sobol_limited_rev(sample_index_rev: u32, dimension: u32, log2_sample_count: u32) -> u32 {
+    assert!(dimension < NUM_DIMENSIONS);
+    debug_assert!(log2_sample_count <= 16);
+
+    // The direction vectors are organized for SIMD, so we
+    // need to access them this way.
+    let dimension_set = (dimension >> 2) as usize;
+    let sub_dimension = (dimension & 0b11) as usize;
+
+    // Compute the Sobol sample with reversed bits.  Unlike `sobol_rev()`
+    // the number of depths varies, so rather than masking every depth we only
+    // visit the set bits of the index.
+    let vecs = &REV_VECTORS[dimension_set];
+    let offset = vecs.len() - log2_sample_count as usize;
+    let mut bits = ((sample_index_rev as u64) >> (32 - log2_sample_count)) as u32;
+    let mut sobol = 0u32;
+    while bits != 0 {
+        sobol ^= vecs[offset + bits.trailing_zeros() as usize][sub_dimension];
+        bits &= bits - 1;
+    }
+
+    sobol
+}
+
+/// Same as [`sobol_limited_rev()`] except returns four dimensions at once.
+///
+/// `dimension_set` is interpreted the same as in [`sobol_int4_rev()`].
+#[inline]
+pub fn sobol_limited_int4_rev(
+    sample_index_rev: u32,
+    dimension_set: u32,
+    log2_sample_count: u32,
+) -> Int4 {
+    assert!(dimension_set < NUM_DIMENSION_SETS_4D);
+    debug_assert!(log2_sample_count <= 16);
+
+    // Compute the Sobol sample with reversed bits.  See `sobol_limited_rev()`
+    // for why this only visits the set bits of the index.
+    let vecs = &REV_VECTORS[dimension_set as usize];
+    let offset = vecs.len() - log2_sample_count as usize;
+    let mut bits = ((sample_index_rev as u64) >> (32 - log2_sample_count)) as u32;
+    let mut sobol = Int4::zero();
+    while bits != 0 {
+        sobol ^= vecs[offset + bits.trailing_zeros() as usize].into();
+        bits &= bits - 1;
+    }
+
+    sobol
+}
+
 /// Scramble `n` using a hash function that closely approximates a
 /// reverse-bit Owen scramble.
 ///
@@ -134,11 +321,426 @@ pub fn owen_scramble_int4_rev(mut n_rev: Int4, scramble: Int4) -> Int4 {
     n_rev
 }
 
+/// The inverse of [`owen_scramble_rev()`].
+///
+/// For any `n_rev` and `scramble`,
+/// `owen_scramble_inverse_rev(owen_scramble_rev(n_rev, scramble), scramble)`
+/// is `n_rev`.  This works because bit `p` of the scrambled output only
+/// depends on bits `0..=p` of the input, and always flips when input bit `p`
+/// flips, so the input can be recovered one bit at a time.
+///
+/// This is much slower than [`owen_scramble_rev()`], since it evaluates
+/// the scramble once per bit.
+#[inline]
+pub fn owen_scramble_inverse_rev(n_rev: u32, scramble: u32) -> u32 {
+    let mut result = 0u32;
+    for p in 0..32 {
+        let bit = 1 << p;
+        if (owen_scramble_rev(result, scramble) ^ n_rev) & bit != 0 {
+            result |= bit;
+        }
+    }
+
+    result
+}
+
+/// A hash function for approximating reverse-bit Owen scrambles.
+///
+/// Implementors provide the same operation as [`owen_scramble_rev()`] and
+/// [`owen_scramble_int4_rev()`], but with a different hash.  This lets
+/// samplers like [`CustomSampler`](crate::CustomSampler) trade quality
+/// against speed, or reproduce the output of a specific paper.
+///
+/// To be a valid reverse-bit Owen scramble, bit `p` of the output may only
+/// depend on bits `0..=p` of the input, and must always flip when bit `p` of
+/// the input flips.
+pub trait OwenHash {
+    /// Scramble `n_rev` with the given random `scramble` value.
+    ///
+    /// `scramble` must already be well randomized, the same as for
+    /// [`owen_scramble_rev()`].
+    fn scramble_rev(n_rev: u32, scramble: u32) -> u32;
+
+    /// Same as [`scramble_rev()`](OwenHash::scramble_rev), except on 4
+    /// integers at a time.
+    fn scramble_int4_rev(n_rev: Int4, scramble: Int4) -> Int4;
+}
+
+/// The Laine-Karras style hash from Brent Burley's paper "Practical
+/// Hash-based Owen Scrambling", 2020.
+///
+/// Noticeably worse mixing of the lower bits than [`BetterLkHash`], which
+/// was designed to fix that.  Mainly useful for comparing against the
+/// paper's hash.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct LkHash;
+
+impl OwenHash for LkHash {
+    #[inline(always)]
+    fn scramble_rev(mut n_rev: u32, scramble: u32) -> u32 {
+        n_rev = n_rev.wrapping_add(scramble);
+        n_rev ^= n_rev.wrapping_mul(0x6c50b47c);
+        n_rev ^= n_rev.wrapping_mul(0xb82f1e52);
+        n_rev ^= n_rev.wrapping_mul(0xc7afe638);
+        n_rev ^= n_rev.wrapping_mul(0x8d22f6e6);
+
+        n_rev
+    }
+
+    #[inline(always)]
+    fn scramble_int4_rev(mut n_rev: Int4, scramble: Int4) -> Int4 {
+        n_rev += scramble;
+        n_rev ^= n_rev * [0x6c50b47c; 4].into();
+        n_rev ^= n_rev * [0xb82f1e52; 4].into();
+        n_rev ^= n_rev * [0xc7afe638; 4].into();
+        n_rev ^= n_rev * [0x8d22f6e6; 4].into();
+
+        n_rev
+    }
+}
+
+/// The hash used by [`owen_scramble_rev()`], and by the main samplers in
+/// this crate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BetterLkHash;
+
+impl OwenHash for BetterLkHash {
+    #[inline(always)]
+    fn scramble_rev(n_rev: u32, scramble: u32) -> u32 {
+        owen_scramble_rev(n_rev, scramble)
+    }
+
+    #[inline(always)]
+    fn scramble_int4_rev(n_rev: Int4, scramble: Int4) -> Int4 {
+        owen_scramble_int4_rev(n_rev, scramble)
+    }
+}
+
+/// Two rounds of [`BetterLkHash`] with decorrelated scramble values.
+///
+/// Roughly half the speed of [`BetterLkHash`].  The second round is a
+/// conservative choice for when the quality of a single round is in doubt,
+/// but it isn't known to get measurably closer to a true Owen scramble: on
+/// the statistical tests in this crate, both are indistinguishable from
+/// [`ExactOwen`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct DoubleLkHash;
+
+impl OwenHash for DoubleLkHash {
+    #[inline(always)]
+    fn scramble_rev(n_rev: u32, scramble: u32) -> u32 {
+        let n_rev = owen_scramble_rev(n_rev, scramble);
+        owen_scramble_rev(n_rev, hash(scramble))
+    }
+
+    #[inline(always)]
+    fn scramble_int4_rev(n_rev: Int4, scramble: Int4) -> Int4 {
+        let n_rev = owen_scramble_int4_rev(n_rev, scramble);
+        owen_scramble_int4_rev(n_rev, hash_int4(scramble))
+    }
+}
+
+/// A true Owen scramble, via [`owen_scramble_exact_rev()`].
+///
+/// Many times slower than the other hashes, but exact.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ExactOwen;
+
+impl OwenHash for ExactOwen {
+    #[inline(always)]
+    fn scramble_rev(n_rev: u32, scramble: u32) -> u32 {
+        owen_scramble_exact_rev(n_rev, scramble)
+    }
+
+    #[inline(always)]
+    fn scramble_int4_rev(n_rev: Int4, scramble: Int4) -> Int4 {
+        owen_scramble_exact_int4_rev(n_rev, scramble)
+    }
+}
+
+/// Scramble `n` with a true reverse-bit Owen scramble.
+///
+/// Unlike [`owen_scramble_rev()`], which approximates an Owen scramble with a
+/// fast hash, this does a random bit flip at every node of the binary tree
+/// of digits, which is exactly what Owen's nested uniform scrambling
+/// describes.  Each node's flip is derived by hashing `scramble` together
+/// with the node's prefix (the digits above it), so the result is a
+/// deterministic function of `n` and `scramble`.
+///
+/// This is much slower than [`owen_scramble_rev()`], and is mainly meant as
+/// a reference for validating the approximation, or for applications that
+/// need the exact statistical guarantees of Owen scrambling.
+///
+/// **IMPORTANT:** `scramble` must already be well randomized, the same as
+/// for [`owen_scramble_rev()`].
+#[inline]
+pub fn owen_scramble_exact_rev(n_rev: u32, scramble: u32) -> u32 {
+    let mut result = n_rev;
+    for p in 0..32 {
+        // The digits above this one, plus a marker bit so that nodes at
+        // different depths get different keys.
+        let node = (n_rev & !(!0u32 << p)) | (1 << p);
+        let flip = hash(hash(node) ^ scramble) >> 31;
+        result ^= flip << p;
+    }
+
+    result
+}
+
+/// Same as [`owen_scramble_exact_rev()`], except on 4 integers at a time.
+///
+/// You can (and probably should) put a different random scramble value
+/// in each lane of `scramble` to scramble each lane differently.
+#[inline]
+pub fn owen_scramble_exact_int4_rev(n_rev: Int4, scramble: Int4) -> Int4 {
+    let mut result = n_rev;
+    for p in 0..32 {
+        let node = (n_rev & Int4::splat(!(!0u32 << p))) | Int4::splat(1 << p);
+        let flip = hash_int4(hash_int4(node) ^ scramble) >> 31;
+        result ^= flip << p;
+    }
+
+    result
+}
+
+/// Scramble `n` with a reverse-bit random linear scramble, as described by
+/// Matoušek.
+///
+/// This multiplies the digits of `n` by a random lower-triangular binary
+/// matrix with ones on the diagonal, and then applies a random digital shift.
+/// Both the matrix and the shift are derived from `scramble`.  It is cheaper
+/// but weaker than a full Owen scramble ([`owen_scramble_rev()`]): it
+/// preserves the net properties of the sequence, but the scrambled digits of
+/// a point only depend linearly on its higher digits.
+///
+/// See "On the L2-discrepancy for anchored boxes" by Jiří Matoušek, 1998.
+///
+/// **IMPORTANT:** `scramble` must already be well randomized, the same as
+/// for [`owen_scramble_rev()`].
+#[inline]
+pub fn linear_scramble_rev(n_rev: u32, scramble: u32) -> u32 {
+    // The matrix columns are generated with a xorshift generator seeded from
+    // the scramble value.
+    let mut column = hash(scramble ^ 0x5b3e1f07) | 1;
+    let mut result = scramble;
+    for i in 0..32 {
+        column ^= column << 13;
+        column ^= column >> 17;
+        column ^= column << 5;
+
+        // Reversed bits, so the lower-triangular part is above the bit.
+        let above = !1u32 << i;
+        let mask = 0u32.wrapping_sub((n_rev >> i) & 1);
+        result ^= mask & ((column & above) | (1 << i));
+    }
+
+    result
+}
+
+/// Same as [`linear_scramble_rev()`], except on 4 integers at a time.
+///
+/// You can (and probably should) put a different random scramble value
+/// in each lane of `scramble` to scramble each lane differently.
+#[inline]
+pub fn linear_scramble_int4_rev(n_rev: Int4, scramble: Int4) -> Int4 {
+    const ZERO: Int4 = Int4::zero();
+    const ONE: Int4 = Int4::one();
+
+    let mut column = hash_int4(scramble ^ [0x5b3e1f07; 4].into()) | ONE;
+    let mut result = scramble;
+    for i in 0..32 {
+        column ^= column << 13;
+        column ^= column >> 17;
+        column ^= column << 5;
+
+        let above = Int4::splat(!1u32 << i);
+        let mask = ZERO - ((n_rev >> i) & ONE);
+        result ^= mask & ((column & above) | Int4::splat(1 << i));
+    }
+
+    result
+}
+
+/// Shuffle `index` within `0..count`, for any `count`.
+///
+/// The result is a random permutation of `0..count`, different for each
+/// `seed`.  It's made from the same Owen-scramble index shuffle as
+/// [`sample()`](crate::sample), restricted to the smallest power of two
+/// that holds `count` indices, and cycle walked (re-shuffled until it lands
+/// in range) to get down to exactly `count`.  The Owen scramble maps the
+/// first 2^k indices onto themselves, so cycle walking takes fewer than two
+/// steps on average.
+///
+/// With the result as the index into an unshuffled Sobol sequence, the
+/// `count` samples are exactly the first `count` points of the sequence, in
+/// random order.  That's the best stratification achievable for a sample
+/// count that isn't a power of two.  In contrast, shuffling with
+/// [`owen_scramble_rev()`] alone gives a random subset of the next power of
+/// two.
+///
+/// # Panics
+///
+/// * Panics if `count` is zero.
+/// * In debug, panics if `count` is greater than 2^16, or if `index` is
+///   greater than or equal to `count`.  In release, an out-of-range `index`
+///   returns an unspecified index in `0..count`.
+#[inline]
+pub fn shuffle_index(index: u32, count: u32, seed: u32) -> u32 {
+    shuffle_index_with::<BetterLkHash>(index, count, hash(seed ^ 0x79c68e4a))
+}
+
+/// Same as [`shuffle_index()`], but with the hash `H` and an already
+/// randomized `scramble` value.
+#[inline]
+pub(crate) fn shuffle_index_with<H: OwenHash>(index: u32, count: u32, scramble: u32) -> u32 {
+    assert!(count != 0);
+    debug_assert!(count <= (1 << 16));
+    debug_assert!(index < count);
+
+    shuffle_index_folded::<H>(index, count, scramble)
+}
+
+/// Same as [`shuffle_index_with()`], but without the debug checks, so that
+/// its handling of out-of-range indices can be tested in debug too.
+#[inline(always)]
+fn shuffle_index_folded<H: OwenHash>(index: u32, count: u32, scramble: u32) -> u32 {
+    // The scramble's lowest reversed bits (the highest index bits) only
+    // depend on each other, so for indices below 2^`bits` they're all the
+    // same, and masking them off leaves a permutation of `0..2^bits`.
+    //
+    // Cycle walking only terminates for indices that start in `0..count`,
+    // since the cycles of the permutation that pass through that range stay
+    // there.  So an out-of-range index is folded into range first.
+    let bits = 32 - (count - 1).leading_zeros();
+    let mask = ((1u64 << bits) - 1) as u32;
+    let mut i = index % count;
+    loop {
+        i = H::scramble_rev(i.reverse_bits(), scramble).reverse_bits() & mask;
+        if i < count {
+            return i;
+        }
+    }
+}
+
+/// Interleave the bits of `x` and `y` into a Morton (Z-order) code.
+///
+/// The bits of `x` go in the even bit positions, and the bits of `y` in the
+/// odd bit positions.
+#[inline]
+pub fn morton_encode_2d(x: u32, y: u32) -> u64 {
+    #[inline(always)]
+    fn spread(n: u32) -> u64 {
+        let mut n = n as u64;
+        n = (n | (n << 16)) & 0x0000ffff0000ffff;
+        n = (n | (n << 8)) & 0x00ff00ff00ff00ff;
+        n = (n | (n << 4)) & 0x0f0f0f0f0f0f0f0f;
+        n = (n | (n << 2)) & 0x3333333333333333;
+        n = (n | (n << 1)) & 0x5555555555555555;
+        n
+    }
+
+    spread(x) | (spread(y) << 1)
+}
+
+/// The inverse of [`morton_encode_2d()`].
+#[inline]
+pub fn morton_decode_2d(code: u64) -> (u32, u32) {
+    #[inline(always)]
+    fn compact(mut n: u64) -> u32 {
+        n &= 0x5555555555555555;
+        n = (n | (n >> 1)) & 0x3333333333333333;
+        n = (n | (n >> 2)) & 0x0f0f0f0f0f0f0f0f;
+        n = (n | (n >> 4)) & 0x00ff00ff00ff00ff;
+        n = (n | (n >> 8)) & 0x0000ffff0000ffff;
+        n = (n | (n >> 16)) & 0x00000000ffffffff;
+        n as u32
+    }
+
+    (compact(code), compact(code >> 1))
+}
+
+/// All 24 permutations of four elements.
+const PERMUTATIONS_4: [[u8; 4]; 24] = [
+    [0, 1, 2, 3],
+    [0, 1, 3, 2],
+    [0, 2, 1, 3],
+    [0, 2, 3, 1],
+    [0, 3, 1, 2],
+    [0, 3, 2, 1],
+    [1, 0, 2, 3],
+    [1, 0, 3, 2],
+    [1, 2, 0, 3],
+    [1, 2, 3, 0],
+    [1, 3, 0, 2],
+    [1, 3, 2, 0],
+    [2, 0, 1, 3],
+    [2, 0, 3, 1],
+    [2, 1, 0, 3],
+    [2, 1, 3, 0],
+    [2, 3, 0, 1],
+    [2, 3, 1, 0],
+    [3, 0, 1, 2],
+    [3, 0, 2, 1],
+    [3, 1, 0, 2],
+    [3, 1, 2, 0],
+    [3, 2, 0, 1],
+    [3, 2, 1, 0],
+];
+
+/// Randomly permute the base-4 digits of the low `num_bits` bits of `n`.
+///
+/// Starting from the most significant digit, each digit is permuted with
+/// one of the 24 permutations of four elements, chosen by hashing `seed`
+/// together with all the digits above it.  This is a base-4 analogue of
+/// Owen scrambling, and like it, maps numbers that share all digits above
+/// some digit to numbers that also share all digits above it.  If
+/// `num_bits` is odd, the lowest bit is treated as a lone base-2 digit and
+/// randomly flipped instead.
+///
+/// Bits above `num_bits` are passed through unchanged.  This is the
+/// permutation used by [`ZSampler`](crate::ZSampler) to randomize Morton
+/// codes, as described in "Screen-Space Blue-Noise Sampling via Z-Order" by
+/// Ahmed and Wonka.
+///
+/// # Panics
+///
+/// Panics if `num_bits` is greater than 64.
+#[inline]
+pub fn permute_base4_digits(n: u64, num_bits: u32, seed: u32) -> u64 {
+    assert!(num_bits <= 64);
+
+    // Hash of a node of the digit tree, identified by the digits above it
+    // and the position of the digit below it.
+    let node_hash = |prefix: u64, shift: u32| {
+        hash(hash(hash(seed ^ shift) ^ prefix as u32) ^ (prefix >> 32) as u32)
+    };
+
+    let mut result = n;
+    let mut shift = num_bits;
+    while shift >= 2 {
+        shift -= 2;
+        let digit = (n >> shift) & 3;
+        let prefix = n.checked_shr(shift + 2).unwrap_or(0);
+        let perm_index = (node_hash(prefix, shift) as u64 * 24) >> 32;
+        let perm = &PERMUTATIONS_4[perm_index as usize];
+        result ^= (digit ^ perm[digit as usize] as u64) << shift;
+    }
+    if shift == 1 {
+        // The lone base-2 digit.
+        result ^= (node_hash(n >> 1, 0) >> 31) as u64;
+    }
+
+    result
+}
+
 /// A fast 32-bit hash function.
 ///
 /// From <https://github.com/skeeto/hash-prospector>
+///
+/// This is a `const fn`, so it can also be used to compute hashes at
+/// compile time.
 #[inline(always)]
-pub fn hash(mut n: u32) -> u32 {
+pub const fn hash(mut n: u32) -> u32 {
     n ^= 0xe6fe3beb; // So zero doesn't map to zero.
 
     n ^= n >> 16;
@@ -172,6 +774,14 @@ pub fn u32_to_f32_norm(n: u32) -> f32 {
     f32::from_bits((n >> 9) | 0x3f800000) - 1.0
 }
 
+/// Convert a `u64` to a float in [0.0, 1.0).
+///
+/// This maps the full range of `u64` to the [0, 1) range.
+#[inline(always)]
+pub fn u64_to_f64_norm(n: u64) -> f64 {
+    f64::from_bits((n >> 12) | 0x3ff0000000000000) - 1.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -179,7 +789,9 @@ mod tests {
     #[test]
     pub fn to_norm_f32() {
         assert_eq!(u32_to_f32_norm(0), 0.0);
-        assert!(u32_to_f32_norm(core::u32::MAX) < 1.0);
+        assert!(u32_to_f32_norm(u32::MAX) < 1.0);
+        assert_eq!(u64_to_f64_norm(0), 0.0);
+        assert!(u64_to_f64_norm(u64::MAX) < 1.0);
     }
 
     #[test]
@@ -205,4 +817,277 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    pub fn sobol_limited_rev_01() {
+        for d in [0, 1, 5, 100, 255] {
+            for i in [0u32, 1, 2, 3, 20, 1234, 65535] {
+                let i_rev = i.reverse_bits();
+                assert_eq!(sobol_limited_rev(i_rev, d, 16), sobol_rev(i_rev, d));
+
+                // Only the top `log2_sample_count` bits are computed, and
+                // they match the full computation for the first samples.
+                let a = sobol_limited_rev(i_rev, d, 8).reverse_bits();
+                let b = sobol_rev((i & 0xff).reverse_bits(), d).reverse_bits();
+                assert_eq!(a, b);
+                assert_eq!(a & 0x00ffffff, 0);
+            }
+        }
+    }
+
+    #[test]
+    pub fn sobol_int4_dims_rev_01() {
+        for i in [0u32, 1, 2, 3, 20, 1234, 65535] {
+            let i_rev = i.reverse_bits();
+            for dims in [
+                [0, 1, 2, 3],
+                [8, 9, 10, 11],
+                [2, 3, 4, 5],
+                [0, 5, 17, 200],
+                [7, 7, 0, 255],
+            ] {
+                let a: [u32; 4] = sobol_int4_dims_rev(i_rev, dims).into();
+                for l in 0..4 {
+                    assert_eq!(a[l], sobol_rev(i_rev, dims[l]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn sobol_rev_unchecked_01() {
+        for i in [0u32, 1, 2, 3, 20, 1234, 65535] {
+            let i_rev = i.reverse_bits();
+            for d in 0..NUM_DIMENSIONS {
+                assert_eq!(
+                    unsafe { sobol_rev_unchecked(i_rev, d) },
+                    sobol_rev(i_rev, d)
+                );
+            }
+            for ds in 0..NUM_DIMENSION_SETS_4D {
+                let a: [u32; 4] = unsafe { sobol_int4_rev_unchecked(i_rev, ds) }.into();
+                let b: [u32; 4] = sobol_int4_rev(i_rev, ds).into();
+                assert_eq!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    pub fn linear_scramble_rev_01() {
+        for scramble in [0u32, 1, 0x12345678, 0xdeadbeef] {
+            let scramble = hash(scramble);
+
+            // Matches SIMD.
+            for n in [0u32, 1, 2, 0x80000000, 0xffffffff, 0x0000ffff, 0x12345678] {
+                let a: [u32; 4] =
+                    linear_scramble_int4_rev(Int4::splat(n), Int4::splat(scramble)).into();
+                assert_eq!(a, [linear_scramble_rev(n, scramble); 4]);
+            }
+
+            // Bijective on every level, since the matrix is lower triangular
+            // with ones on the diagonal.
+            let mut seen = [false; 256];
+            for n in 0..256u32 {
+                let m = linear_scramble_rev(n, scramble) & 0xff;
+                assert!(!seen[m as usize]);
+                seen[m as usize] = true;
+            }
+        }
+    }
+
+    #[test]
+    pub fn owen_hash_01() {
+        fn check<H: OwenHash>() {
+            for seed in 0..16u32 {
+                let scramble = hash(seed);
+                for n in [0u32, 1, 0x80000000, 0xffffffff, 0x12345678, hash(seed ^ 7)] {
+                    // Matches SIMD.
+                    let a: [u32; 4] =
+                        H::scramble_int4_rev(Int4::splat(n), Int4::splat(scramble)).into();
+                    assert_eq!(a, [H::scramble_rev(n, scramble); 4]);
+
+                    // Flipping an input bit flips the same output bit and
+                    // leaves the lower bits alone.
+                    for p in 0..32 {
+                        let diff =
+                            H::scramble_rev(n ^ (1 << p), scramble) ^ H::scramble_rev(n, scramble);
+                        assert_eq!(diff & (((2u64 << p) - 1) as u32), 1 << p);
+                    }
+                }
+            }
+        }
+
+        check::<LkHash>();
+        check::<BetterLkHash>();
+        check::<DoubleLkHash>();
+        check::<ExactOwen>();
+    }
+
+    /// Statistics of `H` over many random scrambles: the worst chi-squared
+    /// value (255 degrees of freedom) of the first 8 scrambled digits, and
+    /// the worst deviation from 1/2 of the probability that a digit matches
+    /// between two points that split at a higher digit.
+    fn owen_hash_stats<H: OwenHash>() -> (f64, f64) {
+        const SEEDS: u32 = 4096;
+
+        let mut worst_chi2 = 0.0f64;
+        let mut worst_pair = 0.0f64;
+        for &n in [0u32, 0x5a5a5a5a, 0xffffffff].iter() {
+            let mut counts = [0u32; 256];
+            for s in 0..SEEDS {
+                counts[(H::scramble_rev(n, hash(s)) & 0xff) as usize] += 1;
+            }
+            let expected = SEEDS as f64 / 256.0;
+            let chi2: f64 = counts
+                .iter()
+                .map(|&c| (c as f64 - expected).powi(2) / expected)
+                .sum();
+            worst_chi2 = worst_chi2.max(chi2);
+
+            for p in 0..8 {
+                for q in (p + 1)..(p + 9) {
+                    let mut matches = 0;
+                    for s in 0..SEEDS {
+                        let a = H::scramble_rev(n, hash(s));
+                        let b = H::scramble_rev(n ^ (1 << p), hash(s));
+                        matches += 1 - (((a ^ b) >> q) & 1);
+                    }
+                    let dev = (matches as f64 / SEEDS as f64 - 0.5).abs();
+                    worst_pair = worst_pair.max(dev);
+                }
+            }
+        }
+
+        (worst_chi2, worst_pair)
+    }
+
+    #[test]
+    pub fn owen_scramble_exact_rev_01() {
+        // The exact scramble is a proper nested uniform scramble.
+        let (chi2, pair) = owen_hash_stats::<ExactOwen>();
+        assert!(chi2 < 350.0);
+        assert!(pair < 0.05);
+
+        // The default hash is statistically indistinguishable from it on
+        // these measures, as is the two-round hash.
+        let (chi2, pair) = owen_hash_stats::<BetterLkHash>();
+        assert!(chi2 < 350.0);
+        assert!(pair < 0.05);
+        let (chi2, pair) = owen_hash_stats::<DoubleLkHash>();
+        assert!(chi2 < 350.0);
+        assert!(pair < 0.05);
+
+        // Whereas the original Laine-Karras hash has strongly correlated
+        // digits, which is what motivated the better hash.
+        let (_, pair) = owen_hash_stats::<LkHash>();
+        assert!(pair > 0.1);
+    }
+
+    #[test]
+    pub fn morton_2d_01() {
+        assert_eq!(morton_encode_2d(0, 0), 0);
+        assert_eq!(morton_encode_2d(1, 0), 1);
+        assert_eq!(morton_encode_2d(0, 1), 2);
+        assert_eq!(morton_encode_2d(3, 5), 0b100111);
+        assert_eq!(morton_encode_2d(!0, 0), 0x5555555555555555);
+        for &(x, y) in [(0u32, 0u32), (1, 2), (12345, 54321), (!0, 7), (!0, !0)].iter() {
+            assert_eq!(morton_decode_2d(morton_encode_2d(x, y)), (x, y));
+        }
+    }
+
+    #[test]
+    pub fn permute_base4_digits_01() {
+        for seed in 0..8 {
+            for &bits in [0u32, 1, 6, 7].iter() {
+                // Bijective, and keeps numbers that share their higher digits
+                // together.
+                let count = 1usize << bits;
+                let low = 2 - (bits & 1);
+                let mut seen = [false; 128];
+                for n in 0..count as u64 {
+                    let m = permute_base4_digits(n, bits, seed);
+                    assert!(!seen[m as usize]);
+                    seen[m as usize] = true;
+                    if bits >= 2 {
+                        let base = permute_base4_digits(n >> low << low, bits, seed);
+                        assert_eq!(m >> low, base >> low);
+                    }
+                }
+            }
+
+            // Higher bits pass through unchanged.
+            let n = 0xabcd_0000_0000_1234u64;
+            assert_eq!(permute_base4_digits(n, 16, seed) >> 16, n >> 16);
+            assert_eq!(permute_base4_digits(n, 0, seed), n);
+            let _ = permute_base4_digits(n, 64, seed);
+        }
+    }
+
+    #[test]
+    pub fn owen_scramble_inverse_rev_01() {
+        for seed in 0..16u32 {
+            let scramble = hash(seed);
+            for n in [0u32, 1, 0x80000000, 0xffffffff, 0x12345678, hash(seed ^ 7)] {
+                let m = owen_scramble_rev(n, scramble);
+                assert_eq!(owen_scramble_inverse_rev(m, scramble), n);
+                assert_eq!(
+                    owen_scramble_rev(owen_scramble_inverse_rev(n, scramble), scramble),
+                    n
+                );
+            }
+        }
+    }
+
+    #[test]
+    pub fn shuffle_index_01() {
+        for seed in 0..4 {
+            for &count in [1, 2, 3, 100, 128, 1000, 1 << 16].iter() {
+                let mut hit = [false; 1 << 16];
+                for i in 0..count {
+                    let n = shuffle_index(i, count, seed);
+                    assert!(n < count);
+                    assert!(!hit[n as usize]);
+                    hit[n as usize] = true;
+                }
+            }
+        }
+
+        // It's actually shuffled, and differently for each seed.
+        let (mut a, mut b) = ([0u32; 8], [0u32; 8]);
+        for i in 0..8 {
+            a[i] = shuffle_index(i as u32, 100, 1);
+            b[i] = shuffle_index(i as u32, 100, 2);
+        }
+        assert_ne!(a, [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn shuffle_index_zero_count() {
+        shuffle_index(0, 0, 1);
+    }
+
+    #[test]
+    pub fn shuffle_index_out_of_range() {
+        // What `shuffle_index()` does in release, where the debug checks
+        // don't catch out-of-range indices.
+        let shuffle = |index, count, seed: u32| {
+            shuffle_index_folded::<BetterLkHash>(index, count, hash(seed ^ 0x79c68e4a))
+        };
+        for seed in 0..16 {
+            for &count in [1, 2, 3, 5, 100, 1000].iter() {
+                for index in 0..count {
+                    assert_eq!(
+                        shuffle(index, count, seed),
+                        shuffle_index(index, count, seed)
+                    );
+                }
+                for index in count..(count + 64) {
+                    assert!(shuffle(index, count, seed) < count);
+                }
+                assert!(shuffle(u32::MAX, count, seed) < count);
+            }
+        }
+    }
 }
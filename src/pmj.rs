@@ -0,0 +1,102 @@
+//! Progressive multi-jittered (0,2) sequences.
+
+use crate::{salted_padded_seed, SeededSampler};
+
+/// Salt for the seeds of the pairs.
+const PAIR_SALT: u32 = 0xd4613eb9;
+
+/// Compute a single 2D sample of a progressive multi-jittered (0,2)
+/// sequence.
+///
+/// PMJ02 sequences, as described in "Progressive Multi-Jittered Sample
+/// Sequences" by Per Christensen, Andrew Kensler, and Charlie Kilpatrick,
+/// have excellent 2D stratification: every aligned block of 2^m samples
+/// (including the first 2^m samples) forms a (0,m,2)-net, so it's
+/// stratified in every set of 2^m equal-area rectangles of power-of-two
+/// dimensions.  As shown in "Stochastic Generation of (t, s) Sample
+/// Sequences" by Andrew Helmer, Per Christensen, and Andrew Kensler, the
+/// first two dimensions of a randomly scrambled Sobol sequence are exactly
+/// such a sequence, so that is how they're computed here.
+///
+/// The result is the same as the first two dimensions of [`sample()`] with
+/// the same `seed`:
+///
+/// ```rust
+/// # use sobol_burley::{pmj02_2d, sample};
+/// let [x, y] = pmj02_2d(5, 42);
+/// assert_eq!([x, y], [sample(5, 0, 42), sample(5, 1, 42)]);
+/// ```
+///
+/// # Panics
+///
+/// In debug, panics if `sample_index` is greater than or equal to 2^16.
+/// In release, returns unspecified floats in the interval [0, 1).
+///
+/// [`sample()`]: crate::sample
+#[inline]
+pub fn pmj02_2d(sample_index: u32, seed: u32) -> [f32; 2] {
+    let ctx = SeededSampler::new(seed).context(sample_index);
+    [ctx.dim(0), ctx.dim(1)]
+}
+
+/// Compute one 2D pair of dimensions of a single sample of padded PMJ02
+/// sequences.
+///
+/// Each `pair` is a separate [`pmj02_2d()`] sequence, with a seed derived
+/// from `seed`.  So each pair has full PMJ02 stratification on its own, but
+/// is only randomly associated with the other pairs (and with the padded
+/// dimensions of [`sample_unbounded()`](crate::sample_unbounded)).  This is
+/// the usual way to use PMJ02 samples for more than two dimensions.  Pair
+/// zero is the same as [`pmj02_2d()`].
+///
+/// # Panics
+///
+/// In debug, panics if `sample_index` is greater than or equal to 2^16.
+/// In release, returns unspecified floats in the interval [0, 1).
+#[inline]
+pub fn pmj02(sample_index: u32, pair: u32, seed: u32) -> [f32; 2] {
+    pmj02_2d(
+        sample_index,
+        salted_padded_seed(seed, pair as u64, PAIR_SALT),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::is_net;
+    use crate::{sample_unbounded, NUM_DIMENSIONS};
+
+    #[test]
+    fn is_02_sequence() {
+        for s in 0..4 {
+            for pair in [0, 1, 7, 1000] {
+                let mut points = [[0.0f32; 2]; 256];
+                for m in 0..=8 {
+                    let n = 1 << m;
+                    for block in [0, 1, 3, 100] {
+                        for (i, p) in points[..n].iter_mut().enumerate() {
+                            *p = pmj02(block * n as u32 + i as u32, pair, s);
+                        }
+                        assert!(is_net(&points[..n]));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pairs_are_different() {
+        for i in 0..16 {
+            assert_eq!(pmj02(i, 0, 3), pmj02_2d(i, 3));
+            assert_ne!(pmj02(i, 1, 3), pmj02_2d(i, 3));
+
+            // Later pairs aren't just re-labelled padded dimensions.
+            for pair in 1..4u64 {
+                let d = pair * NUM_DIMENSIONS as u64;
+                let padded = [sample_unbounded(i, d, 3), sample_unbounded(i, d + 1, 3)];
+                assert_ne!(pmj02(i, pair as u32, 3), padded);
+            }
+        }
+    }
+}
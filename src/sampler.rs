@@ -0,0 +1,271 @@
+//! A stateful sampler for consuming dimensions one after another.
+
+use crate::{padded_seed, SampleContext, SeededSampler, NUM_DIMENSIONS};
+
+/// A stateful sampler that hands out the dimensions of a single sample in
+/// order.
+///
+/// Renderers and other integrators typically consume the dimensions of a
+/// sample one after another (e.g. "lens", then "time", then "first
+/// bounce", ...).  `Sampler` keeps track of which dimension comes next, so
+/// that every call returns fresh, non-overlapping dimensions:
+///
+/// ```rust
+/// # use sobol_burley::Sampler;
+/// let mut sampler = Sampler::new(0, 42);
+///
+/// let lens = sampler.next_2d();
+/// let time = sampler.next_1d();
+/// let bounce = sampler.next_4d();
+/// ```
+///
+/// # Padding policy
+///
+/// Dimensions are padded the same way as in
+/// [`sample_unbounded()`](crate::sample_unbounded).  Once the
+/// [`NUM_DIMENSIONS`] available dimensions have been used up, the sampler
+/// starts over at dimension zero with a new seed derived from the original
+/// one.  Each such round of [`NUM_DIMENSIONS`] dimensions is stratified
+/// within itself, but is only randomly associated with the other rounds.
+/// The first round always uses the seed exactly as given, so it produces
+/// the same values as [`sample()`](crate::sample).
+///
+/// Additionally, to keep each multi-dimensional value stratified within
+/// itself, `next_2d()` and `next_4d()` never straddle a 4d dimension set:
+/// `next_2d()` starts at the next even dimension, and `next_4d()` starts at
+/// the next multiple of four.  Any dimensions skipped this way are simply
+/// left unused.
+///
+/// Dimensions are counted with a `u64`, the same as in
+/// [`sample_unbounded()`](crate::sample_unbounded), so a sampler can hand out
+/// up to 2^64 dimensions.  Getting anywhere near that would take billions of
+/// calls to `advance(u32::MAX)`.
+///
+/// # Sample counts
+///
+/// When the total number of samples is known and isn't a power of two,
+/// [`with_sample_count()`](Sampler::with_sample_count) makes the sampler
+/// compute the same values as [`sample_counted()`](crate::sample_counted),
+/// which are better stratified for such counts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Sampler {
+    sample_index: u32,
+    seed: u32,
+    next_dimension: u64,
+    sample_count: Option<u32>,
+}
+
+impl Sampler {
+    /// Create a sampler for the sample at `sample_index` of the sequence
+    /// with the given `seed`, starting at dimension zero.
+    ///
+    /// The parameters have the same meaning as in [`sample()`](crate::sample).
+    #[inline]
+    pub fn new(sample_index: u32, seed: u32) -> Sampler {
+        Sampler {
+            sample_index,
+            seed,
+            next_dimension: 0,
+            sample_count: None,
+        }
+    }
+
+    /// Create a sampler for the sample at `sample_index` of a sequence of
+    /// exactly `sample_count` samples with the given `seed`, starting at
+    /// dimension zero.
+    ///
+    /// The parameters have the same meaning as in
+    /// [`sample_counted()`](crate::sample_counted), and every dimension is
+    /// computed the same way.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`sample_counted()`](crate::sample_counted), when computing
+    /// dimensions.
+    #[inline]
+    pub fn with_sample_count(sample_index: u32, sample_count: u32, seed: u32) -> Sampler {
+        Sampler {
+            sample_count: Some(sample_count),
+            ..Sampler::new(sample_index, seed)
+        }
+    }
+
+    /// Switch to the sample at `sample_index` of the sequence with the given
+    /// `seed`, and start over at dimension zero.
+    ///
+    /// Typically called at the start of each sample of each pixel, with
+    /// `seed` identifying the pixel.  The sample count, if any, is kept.
+    #[inline]
+    pub fn start_pixel_sample(&mut self, sample_index: u32, seed: u32) {
+        *self = Sampler {
+            sample_count: self.sample_count,
+            ..Sampler::new(sample_index, seed)
+        };
+    }
+
+    /// Skip the next `dimensions` dimensions.
+    ///
+    /// This is useful for keeping the dimensions of later parts of an
+    /// integrator the same regardless of how many dimensions earlier parts
+    /// took.
+    #[inline]
+    pub fn advance(&mut self, dimensions: u32) {
+        self.next_dimension += dimensions as u64;
+    }
+
+    /// The sample index this sampler is computing.
+    #[inline]
+    pub fn sample_index(&self) -> u32 {
+        self.sample_index
+    }
+
+    /// The seed of the sequence this sampler is computing.
+    #[inline]
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    /// The dimension that the next call to `next_1d()` will compute.
+    #[inline]
+    pub fn next_dimension(&self) -> u64 {
+        self.next_dimension
+    }
+
+    /// Compute the next dimension.
+    #[inline]
+    pub fn next_1d(&mut self) -> f32 {
+        let (dimension, seed) = self.take(1);
+        self.context(seed).dim(dimension)
+    }
+
+    /// Compute the next two dimensions.
+    ///
+    /// Starts at the next even dimension, so that both dimensions are in the
+    /// same 4d dimension set.
+    #[inline]
+    pub fn next_2d(&mut self) -> [f32; 2] {
+        let (dimension, seed) = self.take(2);
+        let ctx = self.context(seed);
+        [ctx.dim(dimension), ctx.dim(dimension + 1)]
+    }
+
+    /// Compute the next four dimensions.
+    ///
+    /// Starts at the next multiple of four, so that the dimensions make up a
+    /// single 4d dimension set.  Computed with [`sample_4d()`](crate::sample_4d).
+    #[inline]
+    pub fn next_4d(&mut self) -> [f32; 4] {
+        let (dimension, seed) = self.take(4);
+        self.context(seed).dims_4d(dimension / 4)
+    }
+
+    /// The context of this sampler's sample in the sequence with `seed`.
+    #[inline(always)]
+    fn context(&self, seed: u32) -> SampleContext {
+        let sampler = SeededSampler::new(seed);
+        match self.sample_count {
+            Some(count) => sampler.context_counted(self.sample_index, count),
+            None => sampler.context(self.sample_index),
+        }
+    }
+
+    /// Take the next `n` dimensions, aligned to `n`, and return the first of
+    /// them along with the padded seed to compute them with.
+    #[inline(always)]
+    fn take(&mut self, n: u64) -> (u32, u32) {
+        let dimension = (self.next_dimension + n - 1) & !(n - 1);
+        self.next_dimension = dimension + n;
+
+        let round = dimension / NUM_DIMENSIONS as u64;
+        let d = (dimension % NUM_DIMENSIONS as u64) as u32;
+        (d, padded_seed(self.seed, round))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sample, sample_4d, sample_4d_counted, sample_counted, sample_unbounded};
+
+    #[test]
+    fn first_round_matches_sample() {
+        let mut sampler = Sampler::new(123, 42);
+        for d in 0..NUM_DIMENSIONS {
+            assert_eq!(sampler.next_1d(), sample(123, d, 42));
+        }
+
+        let mut sampler = Sampler::new(123, 42);
+        for ds in 0..(NUM_DIMENSIONS / 4) {
+            assert_eq!(sampler.next_4d(), sample_4d(123, ds, 42));
+        }
+    }
+
+    #[test]
+    fn alignment() {
+        let mut sampler = Sampler::new(5, 7);
+        sampler.next_1d();
+        let a = sampler.next_2d();
+        assert_eq!(a, [sample(5, 2, 7), sample(5, 3, 7)]);
+        assert_eq!(sampler.next_dimension(), 4);
+
+        sampler.next_1d();
+        let b = sampler.next_4d();
+        assert_eq!(b, sample_4d(5, 2, 7));
+        assert_eq!(sampler.next_dimension(), 12);
+
+        sampler.advance(3);
+        assert_eq!(sampler.next_1d(), sample(5, 15, 7));
+    }
+
+    #[test]
+    fn many_dimensions() {
+        // Far more dimensions than fit in a `u32`.
+        let mut sampler = Sampler::new(9, 3);
+        for _ in 0..3 {
+            sampler.advance(u32::MAX);
+        }
+        let d = u32::MAX as u64 * 3;
+        assert_eq!(sampler.next_dimension(), d);
+        assert_eq!(sampler.next_1d(), sample_unbounded(9, d, 3));
+        assert_eq!(sampler.next_dimension(), d + 1);
+    }
+
+    #[test]
+    fn padding() {
+        let mut sampler = Sampler::new(9, 3);
+        sampler.advance(NUM_DIMENSIONS);
+        let a = sampler.next_4d();
+        assert_ne!(a, sample_4d(9, 0, 3));
+        assert_eq!(a, sample_4d(9, 0, padded_seed(3, 1)));
+
+        // Different rounds get different seeds.
+        sampler.advance(NUM_DIMENSIONS - 4);
+        let b = sampler.next_4d();
+        assert_ne!(a, b);
+        assert_eq!(b, sample_4d(9, 0, padded_seed(3, 2)));
+    }
+
+    #[test]
+    fn matches_unbounded() {
+        let mut sampler = Sampler::new(9, 3);
+        for d in 0..(NUM_DIMENSIONS as u64 * 3) {
+            assert_eq!(sampler.next_1d(), sample_unbounded(9, d, 3));
+        }
+    }
+
+    #[test]
+    fn sample_count() {
+        let mut sampler = Sampler::with_sample_count(9, 100, 3);
+        for d in 0..8 {
+            assert_eq!(sampler.next_1d(), sample_counted(9, d, 3, 100));
+        }
+        assert_eq!(sampler.next_4d(), sample_4d_counted(9, 2, 3, 100));
+        sampler.advance(NUM_DIMENSIONS);
+        let n = sampler.next_4d();
+        assert_eq!(n, sample_4d_counted(9, 3, padded_seed(3, 1), 100));
+
+        // The count survives starting a new sample.
+        sampler.start_pixel_sample(10, 4);
+        assert_eq!(sampler.next_1d(), sample_counted(10, 0, 4, 100));
+    }
+}
@@ -0,0 +1,272 @@
+//! A 64-bit seed type with hierarchical derivation.
+
+/// A 64-bit seed for producing statistically independent Sobol sequences.
+///
+/// The sampling functions in this crate take a `u32` seed, which is plenty
+/// for most uses.  But when seeds are built from many combined inputs
+/// (e.g. scene, frame, tile, pixel, and pass), it becomes easy to get
+/// accidental collisions.  `Seed` provides a 64-bit seed with a strong mixing
+/// function, built from integers or bytes, and derived hierarchically:
+///
+/// ```rust
+/// # use sobol_burley::{Seed, SeededSampler};
+/// let (scene, frame, x, y) = (7u64, 120u32, 640u32, 360u32);
+///
+/// let seed = Seed::new(scene).child(frame).child((x, y));
+///
+/// // Use all 64 bits of the seed.
+/// let sampler = SeededSampler::from_seed(seed);
+/// let n = sampler.sample(0, 0);
+///
+/// // Or convert to a plain `u32` seed for the other sampling functions.
+/// let n = sobol_burley::sample(0, 0, seed.into());
+/// ```
+///
+/// # Collisions and independence
+///
+/// Seeds are mixed with a 64-bit bijective mixing function (the finalizer
+/// of SplitMix64), so:
+///
+/// * Distinct `u64`s passed to [`Seed::new()`] always produce distinct seeds,
+///   and the same goes for distinct `u32`s and distinct `(u32, u32)`s.
+/// * Each type of input is mixed with its own tag, so equal values of
+///   different types (e.g. `1u32` and `1u64`, or `(0u32, 1u32)` and `1u64`)
+///   produce unrelated seeds.
+/// * Otherwise, distinct inputs (different byte strings, different children
+///   of the same or different parents, etc.) produce seeds that behave like
+///   independent random 64-bit numbers.  Among `n` such seeds the chance of
+///   any collision is roughly `n² / 2^65`, which is about 50% at five
+///   billion seeds.
+/// * Deriving is order dependent: `Seed::new(a).child(b)` and
+///   `Seed::new(b).child(a)` are unrelated seeds, as are `child((x, y))` and
+///   `child((y, x))`.
+///
+/// Sequences produced from different seeds are only randomly associated,
+/// with no stratification or correlation between them.
+///
+/// Note that converting a `Seed` to a `u32` necessarily throws away half of
+/// its bits, leaving the usual 32-bit collision rate (about 50% at 77,000
+/// seeds).  [`SeededSampler::from_seed()`](crate::SeededSampler::from_seed)
+/// uses all 64 bits instead.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Seed(u64);
+
+impl Seed {
+    /// Create a seed from a `u64`.
+    #[inline]
+    pub const fn new(n: u64) -> Seed {
+        Seed(mix(n ^ 0x6a09e667f3bcc908))
+    }
+
+    /// Create a seed from a `u128`.
+    #[inline]
+    pub const fn from_u128(n: u128) -> Seed {
+        Seed(mix(mix(n as u64 ^ 0xbb67ae8584caa73b) ^ (n >> 64) as u64))
+    }
+
+    /// Create a seed from a byte string.
+    pub const fn from_bytes(bytes: &[u8]) -> Seed {
+        // Hash eight bytes at a time, with the remainder zero-padded.
+        let mut h = mix(bytes.len() as u64 ^ 0x3c6ef372fe94f82b);
+        let mut i = 0;
+        while i < bytes.len() {
+            let mut chunk = 0u64;
+            let mut j = 0;
+            while j < 8 && (i + j) < bytes.len() {
+                chunk |= (bytes[i + j] as u64) << (j * 8);
+                j += 1;
+            }
+            h = mix(h ^ chunk);
+            i += 8;
+        }
+
+        Seed(h)
+    }
+
+    /// Derive a child seed.
+    ///
+    /// `key` distinguishes children of the same parent, and can be
+    /// anything that converts to a [`Seed`]: integers, tuples of integers
+    /// (e.g. pixel coordinates), byte strings, or other seeds.
+    #[inline]
+    pub fn child<K: Into<Seed>>(self, key: K) -> Seed {
+        self.child_seed(key.into())
+    }
+
+    /// Same as [`child()`](Seed::child), but as a `const fn` that only takes
+    /// another seed.
+    #[inline]
+    pub const fn child_seed(self, key: Seed) -> Seed {
+        // The parent is permuted before combining, so that combining is not
+        // symmetric in the parent and key.
+        let parent = self.0.wrapping_mul(0xa54ff53a5f1d36f1).rotate_left(23);
+        Seed(mix(parent ^ key.0))
+    }
+
+    /// The full 64-bit value of the seed.
+    #[inline]
+    pub const fn to_u64(self) -> u64 {
+        self.0
+    }
+
+    /// Fold the seed into a `u32`, for use with the functions that take a
+    /// `u32` seed.
+    ///
+    /// This is the same as `u32::from(seed)`.
+    #[inline]
+    pub const fn to_u32(self) -> u32 {
+        (self.0 ^ (self.0 >> 32)) as u32
+    }
+}
+
+impl From<u32> for Seed {
+    #[inline]
+    fn from(n: u32) -> Seed {
+        tagged(n as u64, 0x510e527fade682d1)
+    }
+}
+
+impl From<u64> for Seed {
+    #[inline]
+    fn from(n: u64) -> Seed {
+        Seed::new(n)
+    }
+}
+
+impl From<u128> for Seed {
+    #[inline]
+    fn from(n: u128) -> Seed {
+        Seed::from_u128(n)
+    }
+}
+
+impl From<(u32, u32)> for Seed {
+    #[inline]
+    fn from((a, b): (u32, u32)) -> Seed {
+        tagged(((a as u64) << 32) | b as u64, 0x9b05688c2b3e6c1f)
+    }
+}
+
+impl From<(u64, u64)> for Seed {
+    #[inline]
+    fn from((a, b): (u64, u64)) -> Seed {
+        Seed(mix(tagged(b, 0x1f83d9abfb41bd6b).0 ^ a))
+    }
+}
+
+impl From<&[u8]> for Seed {
+    #[inline]
+    fn from(bytes: &[u8]) -> Seed {
+        Seed::from_bytes(bytes)
+    }
+}
+
+impl From<&str> for Seed {
+    #[inline]
+    fn from(s: &str) -> Seed {
+        Seed::from_bytes(s.as_bytes())
+    }
+}
+
+impl From<Seed> for u32 {
+    #[inline]
+    fn from(seed: Seed) -> u32 {
+        seed.to_u32()
+    }
+}
+
+/// Mix `n` with a per-type `tag`.
+///
+/// Still a bijection in `n`, but mixing twice means that the inputs that
+/// collide with those of [`Seed::new()`] or of other tags are scattered
+/// randomly, rather than being e.g. the same small integers.
+#[inline(always)]
+const fn tagged(n: u64, tag: u64) -> Seed {
+    Seed(mix(mix(n ^ tag) ^ tag))
+}
+
+/// A bijective 64-bit mixing function.
+///
+/// The finalizer from SplitMix64, by Sebastiano Vigna.
+#[inline(always)]
+const fn mix(mut n: u64) -> u64 {
+    n ^= n >> 30;
+    n = n.wrapping_mul(0xbf58476d1ce4e5b9);
+    n ^= n >> 27;
+    n = n.wrapping_mul(0x94d049bb133111eb);
+    n ^= n >> 31;
+
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derivation() {
+        let root = Seed::new(7);
+        assert_eq!(root.child(3u32), Seed::new(7).child(3u32));
+        assert_ne!(root.child(3u32), root.child(4u32));
+        assert_ne!(root.child(3u32), Seed::new(3).child(7u32));
+        assert_ne!(root.child((1u32, 2u32)), root.child((2u32, 1u32)));
+        assert_ne!(root.child(1u32).child(2u32), root.child(2u32).child(1u32));
+        assert_eq!(
+            root.child("beauty"),
+            root.child_seed(Seed::from_bytes(b"beauty"))
+        );
+    }
+
+    #[test]
+    fn bytes() {
+        assert_ne!(Seed::from_bytes(b""), Seed::from_bytes(b"\0"));
+        assert_ne!(Seed::from_bytes(b"abc"), Seed::from_bytes(b"abc\0"));
+        assert_ne!(
+            Seed::from_bytes(b"12345678"),
+            Seed::from_bytes(b"123456789")
+        );
+        assert_eq!(Seed::from("abc"), Seed::from_bytes(b"abc"));
+    }
+
+    #[test]
+    fn types_dont_collide() {
+        for n in 0..256u32 {
+            let a = Seed::from(n);
+            let b = Seed::from(n as u64);
+            let c = Seed::from((0u32, n));
+            let d = Seed::from(n as u128);
+            let e = Seed::from((0u64, n as u64));
+            let all = [a, b, c, d, e];
+            for i in 0..all.len() {
+                for j in (i + 1)..all.len() {
+                    assert_ne!(all[i], all[j]);
+                }
+            }
+        }
+        assert_ne!(Seed::from((1u32, 2u32)), Seed::from((1u64 << 32) | 2));
+        assert_ne!(Seed::from((1u64, 2u64)), Seed::from((1u128 << 64) | 2));
+    }
+
+    #[test]
+    fn wide_integers() {
+        assert_ne!(Seed::from_u128(1), Seed::from_u128(1 << 64));
+        assert_ne!(Seed::from((1u64, 0u64)), Seed::from((0u64, 1u64)));
+    }
+
+    #[test]
+    fn bit_balance() {
+        // Each bit of consecutive children should be set about half the
+        // time.
+        let root = Seed::new(0);
+        let mut counts = [0u32; 64];
+        for i in 0..4096u32 {
+            let s = root.child(i).to_u64();
+            for (b, count) in counts.iter_mut().enumerate() {
+                *count += ((s >> b) & 1) as u32;
+            }
+        }
+        for &count in counts.iter() {
+            assert!(count > 1848 && count < 2248);
+        }
+    }
+}
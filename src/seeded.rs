@@ -0,0 +1,564 @@
+//! Samplers with pre-computed seed-dependent state.
+
+use crate::parts::{
+    hash, hash_int4, owen_scramble_int4_rev, owen_scramble_rev, shuffle_index_with,
+    sobol_int4_dims_rev, sobol_int4_rev, sobol_int4_rev_unchecked, sobol_limited_int4_rev,
+    sobol_limited_rev, sobol_rev, sobol_rev_unchecked, u32_to_f32_norm, BetterLkHash, Int4,
+};
+use crate::{Seed, NUM_DIMENSIONS};
+
+/// A Sobol sampler bound to a single seed.
+///
+/// Computes exactly the same values as [`sample()`](crate::sample) and
+/// [`sample_4d()`](crate::sample_4d), but pre-computes the parts of those
+/// functions that only depend on the seed.  This is useful when taking many
+/// samples with the same seed, such as when rendering a single pixel:
+///
+/// ```rust
+/// # use sobol_burley::{sample, sample_4d, SeededSampler};
+/// let sampler = SeededSampler::new(42);
+///
+/// for i in 0..256 {
+///     assert_eq!(sampler.sample(i, 5), sample(i, 5, 42));
+///     assert_eq!(sampler.sample_4d(i, 3), sample_4d(i, 3, 42));
+/// }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SeededSampler {
+    /// Scramble value for shuffling the sample index.
+    pub(crate) index_scramble: u32,
+
+    /// The seed, pre-mixed for computing the per-dimension scramble values.
+    pub(crate) seed_mix: u32,
+}
+
+impl SeededSampler {
+    /// Create a sampler for the given seed.
+    ///
+    /// `seed` has the same meaning as in [`sample()`](crate::sample).
+    #[inline]
+    pub fn new(seed: u32) -> SeededSampler {
+        SeededSampler {
+            index_scramble: hash(seed ^ 0x79c68e4a),
+            // The multiply on `seed` is to avoid accidental cancellation
+            // with `dimension` on an incrementing or otherwise structured
+            // seed.
+            seed_mix: seed.wrapping_mul(0x9c8f2d3b),
+        }
+    }
+
+    /// Create a sampler for the given 64-bit [`Seed`].
+    ///
+    /// Unlike converting the seed to a `u32` and passing it to
+    /// [`new()`](SeededSampler::new), this uses all 64 bits of the seed: 32
+    /// bits for shuffling the sample index, and 32 bits for scrambling the
+    /// dimensions.  See [`Seed`] for the resulting collision properties.
+    ///
+    /// The produced sequences have the same properties as the sequences of
+    /// [`sample()`](crate::sample), but are different sequences than any
+    /// `u32` seed produces.
+    #[inline]
+    pub fn from_seed(seed: Seed) -> SeededSampler {
+        let n = seed.to_u64();
+        SeededSampler {
+            // Both halves of the seed are already well mixed, so no further
+            // hashing is needed.
+            index_scramble: (n >> 32) as u32,
+            seed_mix: n as u32,
+        }
+    }
+
+    /// Compute one dimension of a single sample in the Sobol sequence.
+    ///
+    /// Identical to [`sample()`](crate::sample) with this sampler's seed.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`sample()`](crate::sample).
+    #[inline]
+    pub fn sample(&self, sample_index: u32, dimension: u32) -> f32 {
+        self.context(sample_index).dim(dimension)
+    }
+
+    /// Compute four dimensions of a single sample in the Sobol sequence.
+    ///
+    /// Identical to [`sample_4d()`](crate::sample_4d) with this sampler's
+    /// seed.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`sample_4d()`](crate::sample_4d).
+    #[inline]
+    pub fn sample_4d(&self, sample_index: u32, dimension_set: u32) -> [f32; 4] {
+        self.context(sample_index).dims_4d(dimension_set)
+    }
+
+    /// Compute four arbitrary dimensions of a single sample in the Sobol
+    /// sequence.
+    ///
+    /// Identical to [`sample_4d_dims()`](crate::sample_4d_dims) with this
+    /// sampler's seed.
+    #[inline]
+    pub fn sample_4d_dims(&self, sample_index: u32, dimensions: [u32; 4]) -> [f32; 4] {
+        self.context(sample_index).gather_4d(dimensions)
+    }
+
+    /// Compute `N` consecutive dimensions of a single sample in the Sobol
+    /// sequence, starting at `first_dimension`.
+    ///
+    /// Identical to [`sample_nd()`](crate::sample_nd) with this sampler's
+    /// seed.
+    #[inline]
+    pub fn sample_nd<const N: usize>(&self, sample_index: u32, first_dimension: u32) -> [f32; N] {
+        self.context(sample_index).dims_nd(first_dimension)
+    }
+
+    /// Same as [`sample()`](SeededSampler::sample), but only for sequences of
+    /// up to 2^`log2_sample_count` samples.
+    ///
+    /// Identical to [`sample_limited()`](crate::sample_limited) with this
+    /// sampler's seed.
+    #[inline]
+    pub fn sample_limited(&self, sample_index: u32, dimension: u32, log2_sample_count: u32) -> f32 {
+        debug_assert!(sample_index < (1 << log2_sample_count));
+
+        let sobol = sobol_limited_rev(self.shuffle_rev(sample_index), dimension, log2_sample_count);
+        let sobol_owen_rev =
+            owen_scramble_rev(sobol, hash(scramble_value(self.seed_mix, dimension)));
+
+        u32_to_f32_norm(sobol_owen_rev.reverse_bits())
+    }
+
+    /// Same as [`sample_4d()`](SeededSampler::sample_4d), but only for
+    /// sequences of up to 2^`log2_sample_count` samples.
+    ///
+    /// Identical to [`sample_4d_limited()`](crate::sample_4d_limited) with
+    /// this sampler's seed.
+    #[inline]
+    pub fn sample_4d_limited(
+        &self,
+        sample_index: u32,
+        dimension_set: u32,
+        log2_sample_count: u32,
+    ) -> [f32; 4] {
+        debug_assert!(sample_index < (1 << log2_sample_count));
+
+        let sobol = sobol_limited_int4_rev(
+            self.shuffle_rev(sample_index),
+            dimension_set,
+            log2_sample_count,
+        );
+        let sobol_owen_rev = owen_scramble_int4_rev(
+            sobol,
+            hash_int4(scramble_value_int4(self.seed_mix, dimension_set)),
+        );
+
+        // Un-reverse the bits and convert to floating point in [0, 1).
+        sobol_owen_rev.reverse_bits().to_f32_norm()
+    }
+
+    /// Same as [`sample()`](SeededSampler::sample), but for a sequence of
+    /// exactly `sample_count` samples.
+    ///
+    /// Identical to [`sample_counted()`](crate::sample_counted) with this
+    /// sampler's seed.
+    #[inline]
+    pub fn sample_counted(&self, sample_index: u32, dimension: u32, sample_count: u32) -> f32 {
+        self.context_counted(sample_index, sample_count)
+            .dim(dimension)
+    }
+
+    /// Same as [`sample_4d()`](SeededSampler::sample_4d), but for a
+    /// sequence of exactly `sample_count` samples.
+    ///
+    /// Identical to [`sample_4d_counted()`](crate::sample_4d_counted) with
+    /// this sampler's seed.
+    #[inline]
+    pub fn sample_4d_counted(
+        &self,
+        sample_index: u32,
+        dimension_set: u32,
+        sample_count: u32,
+    ) -> [f32; 4] {
+        self.context_counted(sample_index, sample_count)
+            .dims_4d(dimension_set)
+    }
+
+    /// Create a [`SampleContext`] for computing many dimensions of the
+    /// sample at `sample_index`.
+    ///
+    /// # Panics
+    ///
+    /// In debug, panics if `sample_index` is greater than or equal to 2^16.
+    #[inline]
+    pub fn context(&self, sample_index: u32) -> SampleContext {
+        debug_assert!(sample_index < (1 << 16));
+        self.context_unchecked(sample_index)
+    }
+
+    /// Same as [`context()`](SeededSampler::context), but for a sequence of
+    /// exactly `sample_count` samples, as in
+    /// [`sample_counted()`](crate::sample_counted).
+    ///
+    /// # Panics
+    ///
+    /// Same as [`sample_counted()`](crate::sample_counted).
+    #[inline]
+    pub fn context_counted(&self, sample_index: u32, sample_count: u32) -> SampleContext {
+        let shuffled =
+            shuffle_index_with::<BetterLkHash>(sample_index, sample_count, self.index_scramble);
+        SampleContext {
+            shuffled_rev_index: shuffled.reverse_bits(),
+            seed_mix: self.seed_mix,
+        }
+    }
+
+    /// Same as `context()`, but without checking `sample_index` even in
+    /// debug.
+    #[inline(always)]
+    pub(crate) fn context_unchecked(&self, sample_index: u32) -> SampleContext {
+        SampleContext {
+            shuffled_rev_index: self.shuffle_rev(sample_index),
+            seed_mix: self.seed_mix,
+        }
+    }
+
+    /// Compute one dimension of consecutive samples, starting at
+    /// `first_sample_index`.
+    ///
+    /// `out[i]` is filled with `self.sample(first_sample_index + i, dimension)`.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`sample()`](crate::sample), for every sample index that
+    /// is computed.
+    #[inline]
+    pub fn sample_batch(&self, first_sample_index: u32, dimension: u32, out: &mut [f32]) {
+        // The scramble value is the same for every sample, so it's computed
+        // just once up front.
+        let scramble = hash(scramble_value(self.seed_mix, dimension));
+        for (i, n) in (first_sample_index..).zip(out.iter_mut()) {
+            debug_assert!(i < (1 << 16));
+            let sobol = sobol_rev(self.shuffle_rev(i), dimension);
+            *n = u32_to_f32_norm(owen_scramble_rev(sobol, scramble).reverse_bits());
+        }
+    }
+
+    /// Compute four dimensions of consecutive samples, starting at
+    /// `first_sample_index`.
+    ///
+    /// `out[i]` is filled with
+    /// `self.sample_4d(first_sample_index + i, dimension_set)`.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`sample_4d()`](crate::sample_4d), for every sample index
+    /// that is computed.
+    #[inline]
+    pub fn sample_4d_batch(
+        &self,
+        first_sample_index: u32,
+        dimension_set: u32,
+        out: &mut [[f32; 4]],
+    ) {
+        // The scramble values are the same for every sample, so they're
+        // computed just once up front.
+        let scramble = hash_int4(scramble_value_int4(self.seed_mix, dimension_set));
+        for (i, n) in (first_sample_index..).zip(out.iter_mut()) {
+            debug_assert!(i < (1 << 16));
+            let sobol = sobol_int4_rev(self.shuffle_rev(i), dimension_set);
+            *n = owen_scramble_int4_rev(sobol, scramble)
+                .reverse_bits()
+                .to_f32_norm();
+        }
+    }
+
+    /// Shuffle the index to produce a unique statistically independent
+    /// Sobol sequence for this seed.
+    #[inline(always)]
+    fn shuffle_rev(&self, sample_index: u32) -> u32 {
+        owen_scramble_rev(sample_index.reverse_bits(), self.index_scramble)
+    }
+}
+
+/// One sample of a seeded Sobol sequence, with the sample index already
+/// shuffled.
+///
+/// Computing a dimension of a sample involves shuffling the sample index,
+/// which is the same for every dimension of that sample.  This type does that
+/// shuffling just once up front, making it faster to compute many dimensions
+/// of the same sample.  The results are identical to
+/// [`sample()`](crate::sample) and [`sample_4d()`](crate::sample_4d):
+///
+/// ```rust
+/// # use sobol_burley::{sample, sample_4d, SampleContext};
+/// let ctx = SampleContext::new(7, 42);
+///
+/// for d in 0..40 {
+///     assert_eq!(ctx.dim(d), sample(7, d, 42));
+/// }
+/// for ds in 0..10 {
+///     assert_eq!(ctx.dims_4d(ds), sample_4d(7, ds, 42));
+/// }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SampleContext {
+    /// The reversed-bit sample index, already shuffled.
+    shuffled_rev_index: u32,
+
+    /// The seed, pre-mixed for computing the per-dimension scramble values.
+    seed_mix: u32,
+}
+
+impl SampleContext {
+    /// Create a context for the sample at `sample_index` of the sequence
+    /// with the given `seed`.
+    ///
+    /// The parameters have the same meaning as in [`sample()`](crate::sample).
+    ///
+    /// # Panics
+    ///
+    /// In debug, panics if `sample_index` is greater than or equal to 2^16.
+    #[inline]
+    pub fn new(sample_index: u32, seed: u32) -> SampleContext {
+        SeededSampler::new(seed).context(sample_index)
+    }
+
+    /// Compute one dimension of the sample.
+    ///
+    /// Identical to [`sample()`](crate::sample) with this context's sample
+    /// index and seed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dimension` is greater than or equal to
+    /// [`NUM_DIMENSIONS`](crate::NUM_DIMENSIONS).
+    #[inline]
+    pub fn dim(&self, dimension: u32) -> f32 {
+        let sobol = sobol_rev(self.shuffled_rev_index, dimension);
+        let sobol_owen_rev =
+            owen_scramble_rev(sobol, hash(scramble_value(self.seed_mix, dimension)));
+
+        u32_to_f32_norm(sobol_owen_rev.reverse_bits())
+    }
+
+    /// Compute four dimensions of the sample.
+    ///
+    /// Identical to [`sample_4d()`](crate::sample_4d) with this context's
+    /// sample index and seed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dimension_set` is greater than or equal to
+    /// [`NUM_DIMENSION_SETS_4D`](crate::NUM_DIMENSION_SETS_4D).
+    #[inline]
+    pub fn dims_4d(&self, dimension_set: u32) -> [f32; 4] {
+        let sobol = sobol_int4_rev(self.shuffled_rev_index, dimension_set);
+        let sobol_owen_rev = owen_scramble_int4_rev(
+            sobol,
+            hash_int4(scramble_value_int4(self.seed_mix, dimension_set)),
+        );
+
+        // Un-reverse the bits and convert to floating point in [0, 1).
+        sobol_owen_rev.reverse_bits().to_f32_norm()
+    }
+
+    /// Same as `dim()`, but without bounds checking `dimension`.
+    ///
+    /// # Safety
+    ///
+    /// `dimension` must be less than [`NUM_DIMENSIONS`].
+    #[inline]
+    pub(crate) unsafe fn dim_unchecked(&self, dimension: u32) -> f32 {
+        let sobol = sobol_rev_unchecked(self.shuffled_rev_index, dimension);
+        let sobol_owen_rev =
+            owen_scramble_rev(sobol, hash(scramble_value(self.seed_mix, dimension)));
+
+        u32_to_f32_norm(sobol_owen_rev.reverse_bits())
+    }
+
+    /// Same as `dims_4d()`, but without bounds checking `dimension_set`.
+    ///
+    /// # Safety
+    ///
+    /// `dimension_set` must be less than
+    /// [`NUM_DIMENSION_SETS_4D`](crate::NUM_DIMENSION_SETS_4D).
+    #[inline]
+    pub(crate) unsafe fn dims_4d_unchecked(&self, dimension_set: u32) -> [f32; 4] {
+        let sobol = sobol_int4_rev_unchecked(self.shuffled_rev_index, dimension_set);
+        let sobol_owen_rev = owen_scramble_int4_rev(
+            sobol,
+            hash_int4(scramble_value_int4(self.seed_mix, dimension_set)),
+        );
+
+        // Un-reverse the bits and convert to floating point in [0, 1).
+        sobol_owen_rev.reverse_bits().to_f32_norm()
+    }
+
+    /// Compute four arbitrary dimensions of the sample.
+    ///
+    /// Identical to [`sample_4d_dims()`](crate::sample_4d_dims) with this
+    /// context's sample index and seed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the `dimensions` is greater than or equal to
+    /// [`NUM_DIMENSIONS`](crate::NUM_DIMENSIONS).
+    #[inline]
+    pub fn gather_4d(&self, dimensions: [u32; 4]) -> [f32; 4] {
+        let sobol = sobol_int4_dims_rev(self.shuffled_rev_index, dimensions);
+        let scramble: Int4 = [
+            scramble_value(self.seed_mix, dimensions[0]),
+            scramble_value(self.seed_mix, dimensions[1]),
+            scramble_value(self.seed_mix, dimensions[2]),
+            scramble_value(self.seed_mix, dimensions[3]),
+        ]
+        .into();
+        let sobol_owen_rev = owen_scramble_int4_rev(sobol, hash_int4(scramble));
+
+        // Un-reverse the bits and convert to floating point in [0, 1).
+        sobol_owen_rev.reverse_bits().to_f32_norm()
+    }
+
+    /// Compute `N` consecutive dimensions of the sample, starting at
+    /// `first_dimension`.
+    ///
+    /// Identical to calling [`dim()`](SampleContext::dim) for each
+    /// dimension, but computes as many of them as possible four at a time
+    /// with [`dims_4d()`](SampleContext::dims_4d).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `first_dimension + N` is greater than
+    /// [`NUM_DIMENSIONS`](crate::NUM_DIMENSIONS).
+    #[inline]
+    pub fn dims_nd<const N: usize>(&self, first_dimension: u32) -> [f32; N] {
+        assert!(first_dimension as usize + N <= NUM_DIMENSIONS as usize);
+
+        let mut out = [0.0f32; N];
+
+        // Dimensions before the first full dimension set.
+        let head = (((4 - (first_dimension & 0b11)) & 0b11) as usize).min(N);
+        for i in 0..head {
+            out[i] = self.dim(first_dimension + i as u32);
+        }
+
+        // Full dimension sets.
+        let mut i = head;
+        while i + 4 <= N {
+            let set = self.dims_4d((first_dimension + i as u32) >> 2);
+            out[i..(i + 4)].copy_from_slice(&set);
+            i += 4;
+        }
+
+        // Dimensions after the last full dimension set.
+        for i in i..N {
+            out[i] = self.dim(first_dimension + i as u32);
+        }
+
+        out
+    }
+
+    /// Compute consecutive dimensions of the sample, starting at
+    /// `first_dimension`.
+    ///
+    /// `out[i]` is filled with `self.dim(first_dimension + i)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the computed dimensions is greater than or equal to
+    /// [`NUM_DIMENSIONS`](crate::NUM_DIMENSIONS).
+    #[inline]
+    pub fn dims(&self, first_dimension: u32, out: &mut [f32]) {
+        for (d, n) in (first_dimension..).zip(out.iter_mut()) {
+            *n = self.dim(d);
+        }
+    }
+
+    /// Compute consecutive sets of four dimensions of the sample, starting
+    /// at `first_dimension_set`.
+    ///
+    /// `out[i]` is filled with `self.dims_4d(first_dimension_set + i)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the computed dimension sets is greater than or equal
+    /// to [`NUM_DIMENSION_SETS_4D`](crate::NUM_DIMENSION_SETS_4D).
+    #[inline]
+    pub fn dims_4d_range(&self, first_dimension_set: u32, out: &mut [[f32; 4]]) {
+        for (ds, n) in (first_dimension_set..).zip(out.iter_mut()) {
+            *n = self.dims_4d(ds);
+        }
+    }
+}
+
+/// The (not yet hashed) Owen scramble value for `dimension`.
+#[inline(always)]
+pub(crate) fn scramble_value(seed_mix: u32, dimension: u32) -> u32 {
+    let ds = dimension >> 2;
+    ds ^ seed_mix ^ [0x912f69ba, 0x174f18ab, 0x691e72ca, 0xb40cc1b8][dimension as usize & 0b11]
+}
+
+/// Same as `scramble_value()`, but for a set of four dimensions.
+#[inline(always)]
+pub(crate) fn scramble_value_int4(seed_mix: u32, dimension_set: u32) -> Int4 {
+    let seed: Int4 = [seed_mix; 4].into();
+    let ds: Int4 = [dimension_set; 4].into();
+    seed ^ ds ^ [0x912f69ba, 0x174f18ab, 0x691e72ca, 0xb40cc1b8].into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::is_net;
+
+    #[test]
+    fn batch_matches_single() {
+        for seed in [0u32, 1, 0xdeadbeef] {
+            let sampler = SeededSampler::new(seed);
+
+            let mut out = [0.0f32; 100];
+            sampler.sample_batch(50, 7, &mut out);
+            for (i, n) in out.iter().enumerate() {
+                assert_eq!(*n, sampler.sample(50 + i as u32, 7));
+            }
+
+            let mut out = [[0.0f32; 4]; 100];
+            sampler.sample_4d_batch(50, 3, &mut out);
+            for (i, n) in out.iter().enumerate() {
+                assert_eq!(*n, sampler.sample_4d(50 + i as u32, 3));
+            }
+        }
+    }
+
+    #[test]
+    fn from_seed_is_stratified() {
+        for i in 0..4u64 {
+            let sampler = SeededSampler::from_seed(Seed::new(i));
+            for d in 0..8 {
+                let mut points = [[0.0f32]; 256];
+                for (n, p) in points.iter_mut().enumerate() {
+                    *p = [sampler.sample(n as u32, d)];
+                }
+                assert!(is_net(&points));
+            }
+        }
+    }
+
+    #[test]
+    fn context_ranges_match_single() {
+        let ctx = SampleContext::new(1234, 0xdeadbeef);
+
+        let mut out = [0.0f32; 40];
+        ctx.dims(3, &mut out);
+        for (i, n) in out.iter().enumerate() {
+            assert_eq!(*n, ctx.dim(3 + i as u32));
+        }
+
+        let mut out = [[0.0f32; 4]; 10];
+        ctx.dims_4d_range(2, &mut out);
+        for (i, n) in out.iter().enumerate() {
+            assert_eq!(*n, ctx.dims_4d(2 + i as u32));
+        }
+    }
+}
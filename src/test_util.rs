@@ -0,0 +1,67 @@
+//! Helpers shared by the tests of several modules.
+
+/// The most points [`is_net()`] can check.
+const MAX_POINTS: usize = 1 << 16;
+
+/// Check that `points` are a (0,m,S)-net in base 2, where 2^m is the number
+/// of points.
+///
+/// That is, every box of volume 2^-m whose sides are aligned power-of-two
+/// intervals holds exactly one point.  With `S` of 1, this just checks that
+/// the points are stratified in 2^m equal intervals.
+pub(crate) fn is_net<const S: usize>(points: &[[f32; S]]) -> bool {
+    let n = points.len();
+    assert!(S > 0 && n.is_power_of_two() && n <= MAX_POINTS);
+    let m = n.trailing_zeros();
+
+    // Each box shape is given by the log2 of the number of intervals along
+    // each dimension, which add up to `m`.  The first `S - 1` of them are
+    // counted through all combinations, and the last one is what's left.
+    let mut hits = [false; MAX_POINTS];
+    let mut shape = [0u32; S];
+    loop {
+        let used: u32 = shape[..(S - 1)].iter().sum();
+        if used <= m {
+            shape[S - 1] = m - used;
+            for h in hits[..n].iter_mut() {
+                *h = false;
+            }
+            for p in points.iter() {
+                let mut cell = 0usize;
+                for (&x, &k) in p.iter().zip(shape.iter()) {
+                    cell = (cell << k) | (x * (1u32 << k) as f32) as usize;
+                }
+                if hits[cell] {
+                    return false;
+                }
+                hits[cell] = true;
+            }
+        }
+
+        let mut d = 0;
+        loop {
+            if d == S - 1 {
+                return true;
+            }
+            shape[d] += 1;
+            if shape[d] <= m {
+                break;
+            }
+            shape[d] = 0;
+            d += 1;
+        }
+    }
+}
+
+#[test]
+fn is_net_01() {
+    assert!(is_net(&[[0.3f32]]));
+    assert!(is_net(&[[0.1f32], [0.6]]));
+    assert!(!is_net(&[[0.1f32], [0.4]]));
+
+    // Stratified in each dimension, but not in 2x2 squares.
+    let points = [[0.1f32, 0.1], [0.4, 0.4], [0.6, 0.6], [0.9, 0.9]];
+    assert!(!is_net(&points));
+    let points = [[0.1f32, 0.1], [0.4, 0.6], [0.6, 0.4], [0.9, 0.9]];
+    assert!(is_net(&points));
+}
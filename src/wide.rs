@@ -6,8 +6,8 @@
 pub(crate) mod sse {
     use core::arch::x86_64::{
         __m128i, _mm_add_epi32, _mm_and_si128, _mm_or_si128, _mm_set1_epi32, _mm_set1_ps,
-        _mm_set_epi32, _mm_setzero_si128, _mm_sll_epi32, _mm_slli_epi32, _mm_srl_epi32,
-        _mm_srli_epi32, _mm_sub_epi32, _mm_sub_ps, _mm_xor_si128,
+        _mm_set_epi32, _mm_sll_epi32, _mm_slli_epi32, _mm_srl_epi32, _mm_srli_epi32, _mm_sub_epi32,
+        _mm_sub_ps, _mm_xor_si128,
     };
 
     /// A packed set of four `u32`s.
@@ -42,6 +42,7 @@ pub(crate) mod sse {
             }
         }
 
+        #[allow(dead_code)]
         #[inline(always)]
         pub(crate) const fn ones() -> Int4 {
             Int4 {
@@ -372,6 +373,7 @@ pub(crate) mod fallback {
             Int4 { v: [1u32; 4] }
         }
 
+        #[allow(dead_code)]
         #[inline(always)]
         pub(crate) const fn ones() -> Int4 {
             Int4 {
@@ -0,0 +1,234 @@
+//! A screen-space sampler that spreads one sequence across all pixels.
+
+use crate::parts::{hash, morton_encode_2d, permute_base4_digits};
+use crate::{salted_padded_seed, sample, sample_4d};
+
+/// Salt for re-seeding the blocks of the global sequence.
+const BLOCK_SALT: u32 = 0x3f7a9c25;
+
+/// A sampler that distributes a single Sobol sequence over the pixels of an
+/// image in Z-order.
+///
+/// Using a different seed for each pixel decorrelates the pixels, but the
+/// samples of neighboring pixels then have no stratification with respect
+/// to each other.  `ZSampler` instead follows "Screen-Space Blue-Noise
+/// Sampling via Z-Order" by Abdalla Ahmed and Peter Wonka: each pixel's
+/// samples are a block of one global sequence, with pixels assigned to
+/// blocks along a randomly permuted Morton (Z-order) curve.  The samples of
+/// any aligned square of pixels are then well stratified together, which
+/// pushes the error of the image towards blue noise, especially at low
+/// sample counts.
+///
+/// ```rust
+/// # use sobol_burley::ZSampler;
+/// // A 1920x1080 image with 4 samples per pixel.
+/// let sampler = ZSampler::new(1920, 1080, 2, 42);
+///
+/// for s in 0..4 {
+///     let [x, y, _, _] = sampler.sample_4d(100, 200, s, 0);
+///     let time = sampler.sample(100, 200, s, 4);
+/// }
+/// ```
+///
+/// The Morton code is permuted separately for each 4d dimension set, so
+/// that different dimension sets don't share the same pixel ordering.
+///
+/// Since the sequences of this crate are limited to 2^16 samples, the
+/// global sequence is split into blocks of 2^16 samples, each with its own
+/// seed.  Each block covers an aligned square (or 2:1 rectangle) of pixels,
+/// and the blocks are only randomly associated with each other and with the
+/// padded dimensions of [`sample_unbounded()`](crate::sample_unbounded).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ZSampler {
+    width: u32,
+    height: u32,
+    log2_resolution: u32,
+    log2_samples_per_pixel: u32,
+    seed: u32,
+}
+
+impl ZSampler {
+    /// Create a sampler for an image of `width` by `height` pixels, with
+    /// 2^`log2_samples_per_pixel` samples in each pixel.
+    ///
+    /// `seed` has the same meaning as in [`sample()`], except that it's
+    /// used for the whole image rather than for a single pixel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image is too large for its global sample index to fit
+    /// in 64 bits, that is if `2 * ceil(log2(max(width, height))) +
+    /// log2_samples_per_pixel` is greater than 64.
+    #[inline]
+    pub fn new(width: u32, height: u32, log2_samples_per_pixel: u32, seed: u32) -> ZSampler {
+        let size = width.max(height).max(1);
+        let log2_resolution = 32 - (size - 1).leading_zeros();
+        assert!(log2_resolution as u64 * 2 + log2_samples_per_pixel as u64 <= 64);
+
+        ZSampler {
+            width,
+            height,
+            log2_resolution,
+            log2_samples_per_pixel,
+            seed,
+        }
+    }
+
+    /// Compute one dimension of the sample at index `sample_in_pixel` of
+    /// pixel (`x`, `y`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dimension` is greater than or equal to
+    /// [`NUM_DIMENSIONS`](crate::NUM_DIMENSIONS).
+    ///
+    /// In debug, additionally panics if the pixel is outside of the image or
+    /// `sample_in_pixel` is out of range.
+    #[inline]
+    pub fn sample(&self, x: u32, y: u32, sample_in_pixel: u32, dimension: u32) -> f32 {
+        let (sample_index, seed) = self.index(x, y, sample_in_pixel, dimension / 4);
+        sample(sample_index, dimension, seed)
+    }
+
+    /// Compute four dimensions of the sample at index `sample_in_pixel` of
+    /// pixel (`x`, `y`).
+    ///
+    /// Computes the same values as [`sample()`](ZSampler::sample), with
+    /// `dimension_set` interpreted the same as in [`sample_4d()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dimension_set` is greater than or equal to
+    /// [`NUM_DIMENSION_SETS_4D`](crate::NUM_DIMENSION_SETS_4D).
+    ///
+    /// In debug, additionally panics if the pixel is outside of the image or
+    /// `sample_in_pixel` is out of range.
+    #[inline]
+    pub fn sample_4d(&self, x: u32, y: u32, sample_in_pixel: u32, dimension_set: u32) -> [f32; 4] {
+        let (sample_index, seed) = self.index(x, y, sample_in_pixel, dimension_set);
+        sample_4d(sample_index, dimension_set, seed)
+    }
+
+    /// Compute the sample index and seed of a sample in the global
+    /// sequence.
+    #[inline(always)]
+    fn index(&self, x: u32, y: u32, sample_in_pixel: u32, dimension_set: u32) -> (u32, u32) {
+        debug_assert!(x < self.width && y < self.height);
+        debug_assert!((sample_in_pixel as u64) < (1u64 << self.log2_samples_per_pixel));
+
+        let morton = morton_encode_2d(x, y)
+            .checked_shl(self.log2_samples_per_pixel)
+            .unwrap_or(0)
+            | sample_in_pixel as u64;
+        let index = permute_base4_digits(
+            morton,
+            self.log2_resolution * 2 + self.log2_samples_per_pixel,
+            hash(self.seed ^ hash(dimension_set ^ 0x4e1d7a93)),
+        );
+
+        (
+            index as u32 & 0xffff,
+            salted_padded_seed(self.seed, index >> 16, BLOCK_SALT),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::is_net;
+
+    #[test]
+    fn check_1d_and_4d_match() {
+        let sampler = ZSampler::new(20, 13, 3, 7);
+        for y in 0..13 {
+            for x in 0..20 {
+                for s in 0..8 {
+                    for ds in 0..4 {
+                        let a = [
+                            sampler.sample(x, y, s, ds * 4),
+                            sampler.sample(x, y, s, ds * 4 + 1),
+                            sampler.sample(x, y, s, ds * 4 + 2),
+                            sampler.sample(x, y, s, ds * 4 + 3),
+                        ];
+                        assert_eq!(a, sampler.sample_4d(x, y, s, ds));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pixel_samples_stratified() {
+        // The samples of each pixel are stratified on their own.
+        const K: u32 = 4;
+        let sampler = ZSampler::new(8, 8, K, 3);
+        for y in 0..8 {
+            for x in 0..8 {
+                for d in 0..8 {
+                    let mut points = [[0.0f32]; 1 << K];
+                    for (s, p) in points.iter_mut().enumerate() {
+                        *p = [sampler.sample(x, y, s as u32, d)];
+                    }
+                    assert!(is_net(&points));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pixel_blocks_stratified() {
+        // With one sample per pixel, the samples of each aligned 4x4 block of
+        // pixels form a (0,4,2)-net in the first two dimensions.
+        let sampler = ZSampler::new(16, 16, 0, 9);
+        for by in 0..4 {
+            for bx in 0..4 {
+                let mut points = [[0.0f32; 2]; 16];
+                for (i, p) in points.iter_mut().enumerate() {
+                    let (x, y) = (bx * 4 + (i as u32 % 4), by * 4 + (i as u32 / 4));
+                    *p = [sampler.sample(x, y, 0, 0), sampler.sample(x, y, 0, 1)];
+                }
+                assert!(is_net(&points));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_many_samples() {
+        // Must hit the size check rather than overflowing.
+        ZSampler::new(4, 4, u32::MAX, 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn pixel_outside_image() {
+        // Inside the padded 2048x2048 square, but not the image.
+        ZSampler::new(1920, 1080, 2, 0).sample(1919, 1500, 0, 0);
+    }
+
+    #[test]
+    fn large_images() {
+        // Images with more than 2^16 samples spill over into other seeds.
+        let sampler = ZSampler::new(1 << 16, 1 << 16, 32, 5);
+        let n = sampler.sample_4d((1 << 16) - 1, 12345, !0, 63);
+        for &v in n.iter() {
+            assert!((0.0..1.0).contains(&v));
+        }
+
+        // The later blocks aren't the same sequences as padded dimensions.
+        let sampler = ZSampler::new(512, 512, 0, 5);
+        let mut reseeded = 0;
+        for y in (0..512).step_by(64) {
+            for x in (0..512).step_by(64) {
+                let (_, seed) = sampler.index(x, y, 0, 0);
+                reseeded += (seed != 5) as u32;
+                for round in 1..4 {
+                    assert_ne!(seed, crate::padded_seed(5, round));
+                }
+            }
+        }
+        assert!(reseeded > 0);
+    }
+}